@@ -0,0 +1,40 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use utc_time::{input_lines, parse_input, parse_lines, parse_traced, ParseOptions};
+
+// One input per branch of the format list, from the first tried to the last
+const INPUTS: [(&str, &str); 6] = [
+    ("rfc3339", "2025-09-22T21:00:00+10:00"),
+    ("time_only", "21:00"),
+    ("time_date", "21:00:30 22-09-2025"),
+    ("twelve_hour_slash", "9:00 PM 22/09/25"),
+    ("date_first", "22/09/2025 9:00 PM"),
+    ("unrecognized", "not a time"),
+];
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_input");
+    for (name, input) in INPUTS {
+        group.bench_function(name, |b| b.iter(|| parse_input(black_box(input))));
+    }
+    group.finish();
+}
+
+// A million lines in one layout, late in the probe order: `parse_lines`,
+// which `--stdin` reads through and which tries the last line's pattern
+// first, against a full probe per line
+fn batch(c: &mut Criterion) {
+    let text: String = (0..1_000_000).map(|i| format!("{:02}:{:02} 22/09/2025\n", i / 60 % 24, i % 60)).collect();
+    let opts = ParseOptions::default();
+    let mut group = c.benchmark_group("batch_1m");
+    group.sample_size(10);
+    group.bench_function("parse_lines", |b| b.iter(|| parse_lines(black_box(text.as_bytes()), &opts).count()));
+    group.bench_function("full_probe", |b| {
+        b.iter(|| input_lines(black_box(text.as_bytes())).filter_map(|(_, line)| parse_traced(&line.ok()?, &opts).ok()).count())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse, batch);
+criterion_main!(benches);
@@ -0,0 +1,87 @@
+//! Common timezone abbreviations and the IANA zones they stand for.
+//!
+//! Abbreviations are approximate: most name an offset shared by several
+//! regions, so each maps to one representative zone, and `AEST` in Brisbane
+//! and Sydney only agree in winter. A few (`CST`, `IST`, `AST`) are used by
+//! unrelated zones and map to all of them.
+
+use chrono::{FixedOffset, NaiveDateTime, Offset, TimeDelta, TimeZone};
+use chrono_tz::{OffsetComponents, OffsetName, Tz};
+
+const ABBREVIATIONS: &[(&str, &[Tz])] = &[
+    ("ACDT", &[Tz::Australia__Adelaide]),
+    ("ACST", &[Tz::Australia__Darwin]),
+    ("ADT", &[Tz::America__Halifax]),
+    ("AEDT", &[Tz::Australia__Sydney]),
+    ("AEST", &[Tz::Australia__Brisbane]),
+    ("AKDT", &[Tz::America__Anchorage]),
+    ("AKST", &[Tz::America__Anchorage]),
+    ("ART", &[Tz::America__Argentina__Buenos_Aires]),
+    ("AST", &[Tz::America__Halifax, Tz::Asia__Riyadh]),
+    ("AWST", &[Tz::Australia__Perth]),
+    ("BRT", &[Tz::America__Sao_Paulo]),
+    ("BST", &[Tz::Europe__London]),
+    ("CAT", &[Tz::Africa__Maputo]),
+    ("CDT", &[Tz::America__Chicago]),
+    ("CEST", &[Tz::Europe__Berlin]),
+    ("CET", &[Tz::Europe__Berlin]),
+    ("CST", &[Tz::America__Chicago, Tz::Asia__Shanghai, Tz::America__Havana]),
+    ("EAT", &[Tz::Africa__Nairobi]),
+    ("EDT", &[Tz::America__New_York]),
+    ("EEST", &[Tz::Europe__Athens]),
+    ("EET", &[Tz::Europe__Athens]),
+    ("EST", &[Tz::America__New_York]),
+    ("GMT", &[Tz::Europe__London]),
+    ("HKT", &[Tz::Asia__Hong_Kong]),
+    ("HST", &[Tz::Pacific__Honolulu]),
+    ("ICT", &[Tz::Asia__Bangkok]),
+    ("IST", &[Tz::Asia__Kolkata, Tz::Europe__Dublin, Tz::Asia__Jerusalem]),
+    ("JST", &[Tz::Asia__Tokyo]),
+    ("KST", &[Tz::Asia__Seoul]),
+    ("MDT", &[Tz::America__Denver]),
+    ("MSK", &[Tz::Europe__Moscow]),
+    ("MST", &[Tz::America__Denver]),
+    ("NDT", &[Tz::America__St_Johns]),
+    ("NST", &[Tz::America__St_Johns]),
+    ("NZDT", &[Tz::Pacific__Auckland]),
+    ("NZST", &[Tz::Pacific__Auckland]),
+    ("PDT", &[Tz::America__Los_Angeles]),
+    ("PHT", &[Tz::Asia__Manila]),
+    ("PKT", &[Tz::Asia__Karachi]),
+    ("PST", &[Tz::America__Los_Angeles]),
+    ("SAST", &[Tz::Africa__Johannesburg]),
+    ("SGT", &[Tz::Asia__Singapore]),
+    ("UTC", &[Tz::UTC]),
+    ("WAT", &[Tz::Africa__Lagos]),
+    ("WEST", &[Tz::Europe__Lisbon]),
+    ("WET", &[Tz::Europe__Lisbon]),
+    ("WIB", &[Tz::Asia__Jakarta]),
+];
+
+/// The zones `abbr` may stand for, in order of preference, ignoring case.
+/// Empty when the abbreviation is unknown; more than one entry means it is
+/// ambiguous and the caller should ask for a full name.
+pub fn zones_for(abbr: &str) -> &'static [Tz] {
+    ABBREVIATIONS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(abbr))
+        .map_or(&[], |(_, zones)| zones)
+}
+
+/// The UTC offset `abbr` names in `zone` near `ndt`: the offset in effect at
+/// `ndt` or half a year either side whose tzdb abbreviation matches, else the
+/// zone's standard offset. So `PST` stays `-08:00` even in July.
+pub fn offset_for(abbr: &str, zone: Tz, ndt: NaiveDateTime) -> FixedOffset {
+    let half_year = TimeDelta::days(182);
+    let offsets: Vec<_> = [Some(ndt), ndt.checked_sub_signed(half_year), ndt.checked_add_signed(half_year)]
+        .into_iter()
+        .flatten()
+        .map(|at| zone.offset_from_utc_datetime(&at))
+        .collect();
+    offsets
+        .iter()
+        .find(|o| o.abbreviation().is_some_and(|name| name.eq_ignore_ascii_case(abbr)))
+        .or_else(|| offsets.iter().find(|o| o.dst_offset().is_zero()))
+        .unwrap_or(&offsets[0])
+        .fix()
+}
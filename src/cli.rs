@@ -0,0 +1,819 @@
+//! Command-line parsing for the `utc_time` binary.
+//!
+//! An optional subcommand comes first. After it, flags may appear anywhere;
+//! whatever is left over is the time to convert, given either as one quoted
+//! string or as separate time and date words.
+
+use chrono::{Locale, NaiveTime, TimeDelta};
+use utc_time::{Direction, Input, ParseOptions};
+
+use crate::output::Format;
+use crate::{Ambiguity, ExitCode, Resolve};
+
+/// The full `--help` text.
+pub const HELP: &str = "\
+utc_time - convert times between UTC and a timezone
+
+Usage:
+  utc_time [convert] [OPTIONS] TIME [DATE]
+  utc_time [convert] [OPTIONS] --stdin | --input-file PATH | --repl
+  utc_time now [--tz ZONE,...]         same as --world
+  utc_time diff [OPTIONS] A B          same as --compare A B
+  utc_time list [FILTER]               same as --list-timezones
+
+convert is the default, so `utc_time now` shows the world clock; use
+`utc_time convert now` to convert the current instant. Without TIME a single
+line piped on stdin is converted, e.g. `echo 21:00 | utc_time --to-utc`.
+
+Without --to-utc or --to-local the conversion is picked from a menu when
+stdin is a terminal, and is local -> UTC otherwise or when the input already
+names an instant (epoch, RFC3339, now, or a time with a zone abbreviation).
+
+Input formats:
+  HH:MM[:SS]                  today's date in Brisbane (see --bare-tz)
+  H:MM[:SS] AM|PM             12-hour; 12:00 AM is midnight, 12:00 PM noon. Also
+                              9:00PM, 9:00 pm or 9:00 p.m.
+  noon | midnight             12:00 or 00:00 in place of TIME, e.g. noon 22-09-2025,
+                              midnight tomorrow
+  TIME yyyy-mm-dd             21:00 2025-09-22
+  TIME dd-mm-yyyy | dd-mm-yy  21:00 22-09-2025, 21:00 22-09-25
+  TIME dd/mm/yyyy | dd/mm/yy  21:00 22/09/2025, 21:00 22/09/25
+  DATE TIME                   22-09-2025 21:00, 2025-09-22 21:00 (date first)
+  DATE                        22-09-2025, 2025-09-22: midnight in the zone
+  yyyy-Www-d TIME             2025-W39-1 21:00; ISO week date, d is 1 (Mon) to 7 (Sun),
+                              on either side of the time
+  TIME today|tomorrow|yesterday
+  TIME WEEKDAY                next Friday (or last, with --past)
+  TIME-TIME [DATE]            21:00-23:00 22-09-2025, a range sharing one date
+  [@]EPOCH                    seconds since 1970, may be negative or fractional
+  RFC3339                     2025-09-22T21:00:00Z, 2025-09-22T21:00:00+10:00
+  yyyy-mm-ddTHH:MM[:SS]       2025-09-22T21:00, without an offset: read like TIME DATE
+  yyyymmddTHHMMSS[Z]          20250922T210000 or 20250922210000, ISO basic form;
+                              with Z, as --format ical writes it, it is UTC
+  now                         the current instant
+  date output                 Mon Sep 22 11:00:00 UTC 2025, as `date -u` prints it
+  An upper-case zone abbreviation among the words (21:00 AEST 22-09-2025), or a
+  numeric offset (21:00 +10:00 22-09-2025, also +1000 or +10), fixes the
+  offset, so the input is an instant and no menu is shown.
+  So does a military zone letter on the time, e.g. 21:00Z (UTC) or 21:00K:
+  A-I are +1 to +9, K-M +10 to +12, N-Y -1 to -12, and Z is +0 (no J).
+  Seconds may carry a fraction, e.g. 21:00:30.250, and may be 60 for a leap
+  second (23:59:60), which keeps printing as :60 and has the epoch of :59.
+  Two-digit years 00-68 mean 20xx and 69-99 mean 19xx (see --year-pivot).
+
+Options:
+  --tz ZONE[,ZONE...]   zone to convert with (default: $UTC_TIME_TZ, else the config
+                        file, else Australia/Brisbane);
+                        extra zones get their own output line. `local` is the
+                        system zone ($TZ or the OS setting). Abbreviations
+                        such as AEST or PST are accepted but approximate: each
+                        stands for one representative zone
+  --tz-file PATH        like --tz, reading one zone per line from PATH (`#`
+                        starts a comment)
+  --to-utc              read input as local time in the zone, print UTC
+  --to-local            read input as UTC, print local time in the zone
+  --assume-utc          same as --to-local. Input with an offset (RFC3339, epoch,
+                        a zone abbreviation) keeps its own instant either way
+  --choice 1|2          answer the menu up front: 1 is --to-utc, 2 is --to-local
+  --from ZONE, --to ZONE
+                        convert between two zones; a missing side uses --tz
+  --input-tz ZONE       read the input in ZONE for choice 1 (local -> UTC),
+                        leaving --tz for choice 2
+  --when 'TIME ZONE in ZONE'
+                        the same as --from ZONE --to ZONE TIME, as in
+                        --when '09:00 Brisbane in Europe/London'. The first
+                        zone is the last word before ` in `; either may be an
+                        IANA name, an abbreviation or a city
+  --chain A>B>...       read the input in zone A (or as UTC, with --to-local)
+                        and show that instant in each zone along the chain
+  --earliest, --latest  pick one instant when a DST change repeats the local time
+  --fold-forward        shift a time skipped by a DST change past the gap
+  --format FORMAT       rfc3339 (default), rfc2822, epoch, epoch-ms, date or
+                        ical (the 20250922T110000Z stamp calendar files use).
+                        A comma-separated list, e.g. rfc3339,epoch,ical,
+                        prints the UTC line once per format, in that order
+  --out-format PATTERN  strftime pattern for the output lines; %s is the epoch
+                        in seconds, e.g. '%s (%H:%M)'
+  --precision N         fractional-second digits (0-9) in RFC3339 output and
+                        the human-readable local lines
+  --utc-designator z|offset
+                        end RFC3339 UTC output in Z or +00:00 (the default)
+  --locale CODE         month and weekday names (and AM/PM) in another
+                        language, e.g. de_DE; RFC formats stay English
+  --12h, --24h          clock for the human-readable local line
+  --am-pm-lowercase     write pm rather than PM with --12h
+  --no-offset           leave the numeric offset off the human-readable line
+  --color WHEN          auto (default: only on a terminal), always or never
+  --quiet               print only the target line (UTC for --to-utc, the zone
+                        for --to-local) and no notes
+  --no-newline          leave the newline off the last line printed, as for
+                        capturing one value; earlier lines keep theirs
+  --json                print one JSON object; errors become JSON on stderr
+  --verbose             report which input format matched on stderr, and
+                        flag two-digit years next to the --year-pivot cutoff
+  --validate            only parse the input and print it; no conversion
+  --offset-only         print just the zone's UTC offset at the input time
+  --delta               also print how far the result is from now
+  --relative-to TIME    also print the signed span from TIME, read like the
+                        input, to the result, e.g. +21h or -2d 1h
+  --allow-wrap          let a range end before its start, crossing midnight
+  --recurrence daily|weekly
+                        repeat the input's wall-clock time and list each
+                        occurrence in the target zone, marking any that moves
+                        because an offset changed
+  --count N             occurrences for --recurrence, the first included
+                        (default 4)
+  --calendar-info       also print the day of year and ISO week of the local date
+  --dst-status          also print whether the zone is on standard or daylight
+                        time at the result, with the abbreviation in effect
+  --bizdays-until       also print how many weekdays lie between today and the
+                        result's local date, negative if it is in the past
+  --business-hours HH:MM-HH:MM
+                        also print whether the local result falls in that
+                        window on a weekday (Monday to Friday)
+  --since-midnight      also print seconds since midnight in the target zone
+  --add DURATION        shift the result later, e.g. 90m, 2h, 1d or 1h30m (a day
+                        is 24 hours, so DST changes move the wall clock)
+  --subtract DURATION   shift the result earlier
+  --round UNIT          round input to minute, 15min or hour (halves round up)
+  --year-pivot N        two-digit years up to N mean 20xx (default 68)
+  --ambiguous-year MODE auto (default) picks the century by --year-pivot;
+                        interactive lists the 19xx and 20xx conversions
+  --ambiguous MODE      first (default) reads a numeric date day first; show
+                        also converts its month-first and year-first readings
+  --strict              reject two-digit years instead of picking a century
+  --bare-tz utc|local   take today's date for a bare time (and today, tomorrow,
+                        weekdays) from UTC or the --tz zone (default: Brisbane)
+  --base-date DATE      use DATE (e.g. 2025-09-22 or 22-09-2025) as today for
+                        bare times and relative days; dates in the input win
+  --assume-year YYYY    put a bare time in YYYY, keeping today's month and day
+                        (or --base-date's); relative days keep their year
+  --past                resolve weekday names backwards
+  --epoch-unit UNIT     s (default), ms, us or ns for epoch input
+  --epoch-reference REF count epoch input and the epoch formats from unix
+                        (default, 1970-01-01), j2000 (2000-01-01T00:00Z, Unix
+                        946684800) or filetime (1601-01-01, Unix -11644473600,
+                        in 100 ns ticks whatever --epoch-unit says)
+  --stdin               convert one input per line from stdin
+  --from-clipboard      convert the text on the clipboard (needs a build with
+                        --features clipboard, and pbpaste, wl-paste, xclip,
+                        xsel or PowerShell to read it)
+  --input-file PATH     like --stdin, reading PATH; --json prints one array
+  --group-by-date       with --stdin or --input-file, print the results in time
+                        order under a heading per target date, and the lines
+                        that failed after them
+  --echo-input          with --stdin or --input-file, start each result with
+                        the line it came from and ' -> ', or give it an input
+                        field with --json
+  --log-file PATH       also append each conversion to PATH as a tab-separated
+                        line: run time, input, UTC, local time and zone
+  --csv --column N      convert field N (from 1) of each CSV record on stdin
+                        and write the records back; a record that fails is
+                        passed through, and a first one that fails is kept as
+                        a header
+  --append-column       with --csv, add the result as a new last field instead
+  --repl                convert lines as you type them; `dir` re-asks the
+                        direction and `quit` or EOF ends
+  --compare A B         say which of two inputs is earlier, and by how much
+  --diff-zones A B      say how many hours zone A is ahead of or behind zone B
+                        right now
+  --utc-now             print the current UTC time (honours --format, --precision)
+  --world               show the current time in several zones (or the --tz list)
+  --offset-table        list the result in each --tz zone (or the --world ones),
+                        sorted by UTC offset, to see who is ahead or behind
+  --transitions YEAR    list the zone's offset changes during YEAR
+  --list-timezones [FILTER]
+                        list IANA zone names, optionally matching FILTER
+  --list-formats        list the --format keywords with an example of each
+                        (also --format help)
+  -h, --help            show this help
+
+Config:
+  ~/.config/utc_time/config (or under $XDG_CONFIG_HOME) may set defaults as
+  `key = value` lines: tz, format (a name or strftime pattern), choice (1|2)
+  and default_choice (1|2, what Enter picks at the menu; $UTC_TIME_DEFAULT_CHOICE
+  also sets it). Flags override it.
+
+Examples:
+  utc_time --to-utc 21:00 22-09-2025
+  utc_time --tz Europe/Berlin --to-local 09:30
+  utc_time --from America/New_York --to Asia/Tokyo \"9:00 PM tomorrow\"
+  utc_time --when '9:00 PM tomorrow Brisbane in London'
+  utc_time --format epoch --quiet now
+
+Exit codes:
+  0  success
+  1  usage error
+  2  too many arguments
+  3  input could not be parsed
+  4  local time does not exist (DST gap)
+  5  local time is ambiguous (DST overlap); with --json both readings are
+     printed on stdout under ambiguous
+  6  invalid menu choice
+  7  unknown timezone
+  8  input file could not be read
+  9  stdin closed before a menu choice was made
+  10 the --log-file could not be opened or written
+  11 stdout could not be written, other than by a closed pipe
+";
+
+/// A rejected command line: the exit code to use and what to tell the user.
+#[derive(Debug)]
+pub struct CliError {
+    pub code: ExitCode,
+    pub message: String,
+}
+
+impl CliError {
+    fn usage(message: impl Into<String>) -> CliError {
+        CliError { code: ExitCode::Usage, message: message.into() }
+    }
+}
+
+/// Everything the command line asked for.
+pub struct Cli {
+    /// The time to convert, with separate positional words joined by a space.
+    pub input: Option<String>,
+    /// Zone names stay unresolved so errors can honour `--json`.
+    pub tz: Option<String>,
+    pub tz_file: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Reads the input for choice 1 only; UTC -> local still uses `tz`.
+    pub input_tz: Option<String>,
+    /// The `>`-separated hops of `--chain`, in order.
+    pub chain: Option<String>,
+    pub direction: Option<Direction>,
+    /// What pressing Enter at the menu picks, from the environment or config.
+    pub default_choice: Option<Direction>,
+    /// `None` leaves the choice to the config file.
+    pub format: Option<Format>,
+    /// Every format of a `--format` list, in order; `format` is the first.
+    pub formats: Vec<Format>,
+    pub clock12: bool,
+    pub lower_ampm: bool,
+    pub no_offset: bool,
+    pub color: Color,
+    pub quiet: bool,
+    pub no_newline: bool,
+    pub json: bool,
+    pub verbose: bool,
+    pub validate: bool,
+    pub offset_only: bool,
+    pub delta: bool,
+    pub relative_to: Option<String>,
+    pub calendar_info: bool,
+    pub since_midnight: bool,
+    pub allow_wrap: bool,
+    pub batch: bool,
+    pub input_file: Option<String>,
+    pub group_by_date: bool,
+    pub echo_input: bool,
+    /// Where `--log-file` appends a line per conversion.
+    pub log_file: Option<String>,
+    pub world: bool,
+    pub utc_now: bool,
+    pub repl: bool,
+    pub from_clipboard: bool,
+    pub compare: Option<(String, String)>,
+    pub diff_zones: Option<(String, String)>,
+    pub help: bool,
+    pub list_timezones: bool,
+    pub list_formats: bool,
+    pub list_filter: Option<String>,
+    pub round: Option<TimeDelta>,
+    pub business_hours: Option<(NaiveTime, NaiveTime)>,
+    pub bizdays_until: bool,
+    pub dst_status: bool,
+    pub csv: bool,
+    /// --utc-designator z: RFC3339 output ends in `Z`.
+    pub utc_z: bool,
+    pub transitions: Option<i32>,
+    /// The 1-based field --csv converts.
+    pub column: Option<usize>,
+    pub append_column: bool,
+    /// The sum of --add and --subtract, applied to the converted instant.
+    pub shift: TimeDelta,
+    pub precision: Option<u8>,
+    pub locale: Option<Locale>,
+    pub resolve: Resolve,
+    pub parse_opts: ParseOptions,
+    /// --ambiguous-year interactive: show a two-digit year as 19xx and 20xx.
+    pub both_centuries: bool,
+    /// --ambiguous show: convert each order a numeric date's fields allow.
+    pub all_readings: bool,
+    /// `None` keeps the historical Brisbane date.
+    pub bare_tz: Option<BareTz>,
+    pub offset_table: bool,
+    pub recurrence: Option<Recurrence>,
+    /// Occurrences `--recurrence` lists, the input's own included.
+    pub count: u32,
+}
+
+impl Default for Cli {
+    fn default() -> Self {
+        Cli {
+            input: None,
+            tz: None,
+            tz_file: None,
+            from: None,
+            input_tz: None,
+            to: None,
+            direction: None,
+            default_choice: None,
+            format: None,
+            formats: Vec::new(),
+            clock12: false,
+            lower_ampm: false,
+            no_offset: false,
+            color: Color::Auto,
+            quiet: false,
+            no_newline: false,
+            json: false,
+            verbose: false,
+            validate: false,
+            offset_only: false,
+            delta: false,
+            relative_to: None,
+            chain: None,
+            calendar_info: false,
+            since_midnight: false,
+            allow_wrap: false,
+            batch: false,
+            input_file: None,
+            group_by_date: false,
+            echo_input: false,
+            log_file: None,
+            world: false,
+            utc_now: false,
+            repl: false,
+            from_clipboard: false,
+            compare: None,
+            diff_zones: None,
+            help: false,
+            list_timezones: false,
+            list_formats: false,
+            list_filter: None,
+            round: None,
+            business_hours: None,
+            bizdays_until: false,
+            dst_status: false,
+            csv: false,
+            utc_z: false,
+            transitions: None,
+            column: None,
+            append_column: false,
+            shift: TimeDelta::zero(),
+            precision: None,
+            locale: None,
+            resolve: Resolve::default(),
+            parse_opts: ParseOptions::default(),
+            both_centuries: false,
+            all_readings: false,
+            bare_tz: None,
+            offset_table: false,
+            recurrence: None,
+            count: 4,
+        }
+    }
+}
+
+/// The direction picked by a menu answer, `1` or `2`.
+pub fn direction_from_choice(answer: &str) -> Option<Direction> {
+    match answer {
+        "1" => Some(Direction::ToUtc),
+        "2" => Some(Direction::ToLocal),
+        _ => None,
+    }
+}
+
+/// The menu answer that picks `direction`.
+pub fn choice_for(direction: Direction) -> &'static str {
+    match direction {
+        Direction::ToUtc => "1",
+        Direction::ToLocal => "2",
+    }
+}
+
+/// When to colour the result lines.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Color {
+    /// Only when stdout is a terminal and `NO_COLOR` is unset.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Whose "today" a bare time falls on, for --bare-tz.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BareTz {
+    Utc,
+    /// The zone the input is read in.
+    Local,
+}
+
+impl BareTz {
+    fn parse(name: &str) -> Result<BareTz, String> {
+        match name {
+            "utc" => Ok(BareTz::Utc),
+            "local" => Ok(BareTz::Local),
+            _ => Err(format!("Unknown --bare-tz value '{name}'; expected utc or local")),
+        }
+    }
+}
+
+/// How far apart `--recurrence` repeats the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Recurrence {
+    Daily,
+    Weekly,
+}
+
+impl Recurrence {
+    fn parse(name: &str) -> Result<Recurrence, String> {
+        match name {
+            "daily" => Ok(Recurrence::Daily),
+            "weekly" => Ok(Recurrence::Weekly),
+            _ => Err(format!("Unknown --recurrence '{name}'; expected daily or weekly")),
+        }
+    }
+
+    /// Days between occurrences.
+    pub fn days(self) -> u64 {
+        match self {
+            Recurrence::Daily => 1,
+            Recurrence::Weekly => 7,
+        }
+    }
+
+    /// What one occurrence is called in the output.
+    pub fn unit(self) -> &'static str {
+        match self {
+            Recurrence::Daily => "Day",
+            Recurrence::Weekly => "Week",
+        }
+    }
+}
+
+impl Color {
+    fn parse(name: &str) -> Result<Color, String> {
+        match name {
+            "auto" => Ok(Color::Auto),
+            "always" => Ok(Color::Always),
+            "never" => Ok(Color::Never),
+            _ => Err(format!("Unknown --color value '{name}'; expected auto, always or never")),
+        }
+    }
+}
+
+// The first argument, when it names one; each maps onto a flag
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Command {
+    Convert,
+    List,
+    Now,
+    Diff,
+}
+
+// --business-hours, e.g. `09:00-17:00`; the end may be before the start for
+// a window across midnight
+fn parse_window(window: &str) -> Result<(NaiveTime, NaiveTime), String> {
+    let time = |t: &str| NaiveTime::parse_from_str(t, "%H:%M").ok();
+    window
+        .split_once('-')
+        .and_then(|(start, end)| Some((time(start)?, time(end)?)))
+        .ok_or_else(|| format!("--business-hours expects HH:MM-HH:MM, got '{window}'"))
+}
+
+// --add and --subtract, e.g. `90m`, `2h`, `1d` or `1h30m`; a day is 24 hours
+fn parse_duration(text: &str) -> Result<TimeDelta, String> {
+    let bad = || format!("Invalid duration '{text}'; expected forms like 90m, 2h, 1d or 1h30m");
+    let (mut total, mut digits) = (TimeDelta::zero(), String::new());
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let n: i64 = digits.parse().map_err(|_| bad())?;
+        let part = match c {
+            'd' => TimeDelta::try_days(n),
+            'h' => TimeDelta::try_hours(n),
+            'm' => TimeDelta::try_minutes(n),
+            's' => TimeDelta::try_seconds(n),
+            _ => None,
+        };
+        total = part.and_then(|part| total.checked_add(&part)).ok_or_else(bad)?;
+        digits.clear();
+    }
+    if text.is_empty() || !digits.is_empty() {
+        return Err(bad());
+    }
+    Ok(total)
+}
+
+// --when `TIME ZONE in ZONE`, e.g. `09:00 Brisbane in Europe/London`: the
+// time, the one-word zone it is read in, then the zone to show it in
+fn split_when(phrase: &str) -> Result<(String, String, String), String> {
+    let bad = || format!("--when expects TIME ZONE in ZONE, e.g. '09:00 Brisbane in Europe/London', got '{phrase}'");
+    let (left, to) = phrase.trim().rsplit_once(" in ").ok_or_else(bad)?;
+    let (time, from) = left.trim().rsplit_once(' ').ok_or_else(bad)?;
+    if time.trim().is_empty() || to.trim().is_empty() {
+        return Err(bad());
+    }
+    Ok((time.trim().to_string(), from.to_string(), to.trim().to_string()))
+}
+
+// --round units; exact halves round up (21:00:30 -> 21:01)
+fn parse_round(unit: &str) -> Result<TimeDelta, String> {
+    match unit {
+        "minute" => Ok(TimeDelta::minutes(1)),
+        "15min" => Ok(TimeDelta::minutes(15)),
+        "hour" => Ok(TimeDelta::hours(1)),
+        _ => Err(format!("Unknown --round unit '{unit}'; expected minute, 15min or hour")),
+    }
+}
+
+impl Cli {
+    /// Parses the arguments after the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Cli, CliError> {
+        let mut cli = Cli::default();
+        let mut positional: Vec<String> = Vec::new();
+        let mut when = None;
+        let mut raw = args.into_iter().peekable();
+        let command = raw
+            .next_if(|first| matches!(first.as_str(), "convert" | "list" | "now" | "diff"))
+            .map_or(Command::Convert, |first| match first.as_str() {
+                "list" => Command::List,
+                "now" => Command::Now,
+                "diff" => Command::Diff,
+                _ => Command::Convert,
+            });
+        while let Some(arg) = raw.next() {
+            let mut value = || raw.next().ok_or_else(|| CliError::usage(format!("{arg} needs a value")));
+            match arg.as_str() {
+                "--tz" => cli.tz = Some(value()?),
+                "--tz-file" => cli.tz_file = Some(value()?),
+                "--from" => cli.from = Some(value()?),
+                "--input-tz" => cli.input_tz = Some(value()?),
+                "--chain" => cli.chain = Some(value()?),
+                "--when" => when = Some(split_when(&value()?).map_err(CliError::usage)?),
+                "--to" => cli.to = Some(value()?),
+                "--to-utc" => cli.direction = Some(Direction::ToUtc),
+                "--to-local" | "--assume-utc" => cli.direction = Some(Direction::ToLocal),
+                "--choice" => {
+                    let answer = value()?;
+                    let direction = direction_from_choice(&answer).ok_or_else(|| CliError {
+                        code: ExitCode::BadChoice,
+                        message: format!("Invalid choice '{answer}', expected '1' or '2'"),
+                    })?;
+                    cli.direction = Some(direction);
+                }
+                "--format" => match value()?.as_str() {
+                    "help" => cli.list_formats = true,
+                    list => {
+                        cli.formats = Format::parse_list(list).map_err(CliError::usage)?;
+                        cli.format = cli.formats.first().cloned();
+                    }
+                },
+                "--out-format" => {
+                    cli.format = Some(Format::custom(&value()?).map_err(CliError::usage)?);
+                    cli.formats.clear();
+                }
+                "--12h" => cli.clock12 = true,
+                "--24h" => cli.clock12 = false,
+                "--am-pm-lowercase" => cli.lower_ampm = true,
+                "--no-offset" => cli.no_offset = true,
+                "--color" => cli.color = Color::parse(&value()?).map_err(CliError::usage)?,
+                "--quiet" => cli.quiet = true,
+                "--no-newline" => cli.no_newline = true,
+                "--json" => cli.json = true,
+                "--verbose" => cli.verbose = true,
+                "--validate" => cli.validate = true,
+                "--offset-only" => cli.offset_only = true,
+                "--delta" => cli.delta = true,
+                "--relative-to" => cli.relative_to = Some(value()?),
+                "--calendar-info" => cli.calendar_info = true,
+                "--since-midnight" => cli.since_midnight = true,
+                "--allow-wrap" => cli.allow_wrap = true,
+                "--stdin" => cli.batch = true,
+                "--csv" => cli.csv = true,
+                "--recurrence" => cli.recurrence = Some(Recurrence::parse(&value()?).map_err(CliError::usage)?),
+                "--count" => {
+                    let n = value()?.parse().ok().filter(|&n| n >= 1);
+                    cli.count = n.ok_or_else(|| CliError::usage("--count expects a number from 1"))?;
+                }
+                "--transitions" => {
+                    let year = value()?;
+                    let parsed = year.parse().map_err(|_| CliError::usage(format!("--transitions expects a year, got '{year}'")));
+                    cli.transitions = Some(parsed?);
+                }
+                "--utc-designator" => {
+                    cli.utc_z = match value()?.as_str() {
+                        "z" | "Z" => true,
+                        "offset" => false,
+                        other => return Err(CliError::usage(format!("Unknown --utc-designator '{other}'; expected z or offset"))),
+                    }
+                }
+                "--column" => {
+                    let n = value()?.parse().ok().filter(|n| *n > 0);
+                    cli.column = Some(n.ok_or_else(|| CliError::usage("--column expects a field number from 1"))?);
+                }
+                "--append-column" => cli.append_column = true,
+                "--input-file" => cli.input_file = Some(value()?),
+                "--log-file" => cli.log_file = Some(value()?),
+                "--world" => cli.world = true,
+                "--offset-table" => cli.offset_table = true,
+                "--utc-now" => cli.utc_now = true,
+                "--repl" => cli.repl = true,
+                "--group-by-date" => cli.group_by_date = true,
+                "--echo-input" => cli.echo_input = true,
+                "--from-clipboard" => cli.from_clipboard = true,
+                "--compare" => cli.compare = Some((value()?, value()?)),
+                "--diff-zones" => cli.diff_zones = Some((value()?, value()?)),
+                "--bizdays-until" => cli.bizdays_until = true,
+                "--dst-status" => cli.dst_status = true,
+                "--business-hours" => cli.business_hours = Some(parse_window(&value()?).map_err(CliError::usage)?),
+                "--add" => cli.shift += parse_duration(&value()?).map_err(CliError::usage)?,
+                "--subtract" => cli.shift -= parse_duration(&value()?).map_err(CliError::usage)?,
+                "--round" => cli.round = Some(parse_round(&value()?).map_err(CliError::usage)?),
+                "--earliest" => cli.resolve.ambiguity = Ambiguity::Earliest,
+                "--latest" => cli.resolve.ambiguity = Ambiguity::Latest,
+                "--fold-forward" => cli.resolve.fold_forward = true,
+                "--base-date" => {
+                    let date = value()?;
+                    let parsed = match utc_time::parse(&date) {
+                        Ok(Input::Naive(ndt)) => Ok(ndt.date()),
+                        Ok(Input::Instant(_)) => Err(format!("--base-date takes a plain date, got '{date}'")),
+                        Err(e) => Err(format!("Invalid --base-date '{date}': {e}")),
+                    };
+                    cli.parse_opts.base_date = Some(parsed.map_err(CliError::usage)?);
+                }
+                "--bare-tz" => cli.bare_tz = Some(BareTz::parse(&value()?).map_err(CliError::usage)?),
+                "--past" => cli.parse_opts.past_weekdays = true,
+                "--strict" => cli.parse_opts.strict_years = true,
+                "--assume-year" => {
+                    let year = value()?
+                        .parse::<i32>()
+                        .ok()
+                        .filter(|year| (0..=9999).contains(year))
+                        .ok_or_else(|| CliError::usage("--assume-year expects a year from 0 to 9999, e.g. 2026"))?;
+                    cli.parse_opts.assume_year = Some(year);
+                }
+                "--epoch-unit" => cli.parse_opts.epoch_unit = value()?.parse().map_err(CliError::usage)?,
+                "--epoch-reference" => cli.parse_opts.epoch_reference = value()?.parse().map_err(CliError::usage)?,
+                "--year-pivot" => {
+                    cli.parse_opts.year_pivot = value()?
+                        .parse::<i32>()
+                        .ok()
+                        .filter(|pivot| (0..=99).contains(pivot))
+                        .ok_or_else(|| CliError::usage("--year-pivot expects a number from 0 to 99"))?;
+                }
+                "--ambiguous-year" => {
+                    cli.both_centuries = match value()?.as_str() {
+                        "auto" => false,
+                        "interactive" => true,
+                        other => {
+                            return Err(CliError::usage(format!(
+                                "Unknown --ambiguous-year value '{other}'; expected auto or interactive"
+                            )));
+                        }
+                    };
+                }
+                "--ambiguous" => {
+                    cli.all_readings = match value()?.as_str() {
+                        "first" => false,
+                        "show" => true,
+                        other => {
+                            return Err(CliError::usage(format!("Unknown --ambiguous value '{other}'; expected first or show")));
+                        }
+                    };
+                }
+                "--locale" => {
+                    let code = value()?;
+                    let locale = code
+                        .parse::<Locale>()
+                        .map_err(|_| CliError::usage(format!("Unknown locale '{code}'; expected a code like de_DE or fr_FR")))?;
+                    cli.locale = Some(locale);
+                }
+                "--precision" => {
+                    cli.precision = Some(
+                        value()?
+                            .parse::<u8>()
+                            .ok()
+                            .filter(|digits| *digits <= 9)
+                            .ok_or_else(|| CliError::usage("--precision expects a number from 0 to 9"))?,
+                    );
+                }
+                "-h" | "--help" => cli.help = true,
+                "--list-formats" => cli.list_formats = true,
+                "--list-timezones" => {
+                    cli.list_timezones = true;
+                    cli.list_filter = raw.next_if(|next| !next.starts_with('-'));
+                }
+                flag if flag.starts_with("--") => {
+                    return Err(CliError::usage(format!("Unknown option {flag}")));
+                }
+                _ => positional.push(arg),
+            }
+        }
+
+        let too_many = |message: &str| CliError { code: ExitCode::TooManyArgs, message: message.into() };
+        match command {
+            Command::Convert => {
+                cli.input = match positional.len() {
+                    0 => None,
+                    1 | 2 => Some(positional.join(" ")),
+                    _ => return Err(too_many("Too many arguments; pass either one quoted string or two separate args")),
+                };
+            }
+            Command::List => {
+                if positional.len() > 1 {
+                    return Err(too_many("list takes at most one filter"));
+                }
+                cli.list_timezones = true;
+                cli.list_filter = positional.pop().or(cli.list_filter);
+            }
+            Command::Now => {
+                if !positional.is_empty() {
+                    return Err(too_many("now takes no time; use --tz to pick the zones"));
+                }
+                cli.world = true;
+            }
+            Command::Diff => {
+                let [a, b] = <[String; 2]>::try_from(positional)
+                    .map_err(|_| CliError::usage("diff needs two inputs, e.g. diff 09:00 \"17:30 22-09-2025\""))?;
+                cli.compare = Some((a, b));
+            }
+        }
+        // Sugar for --from ZONE --to ZONE TIME
+        if let Some((time, from, to)) = when {
+            if cli.input.is_some() || cli.from.is_some() || cli.to.is_some() {
+                return Err(CliError::usage("--when names the time and both zones; drop the others"));
+            }
+            (cli.input, cli.from, cli.to) = (Some(time), Some(from), Some(to));
+        }
+        Ok(cli)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, CliError> {
+        Cli::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn base_date_takes_a_plain_date() {
+        let cli = parse(&["--base-date", "22-09-2025", "21:00"]).unwrap();
+        assert_eq!(cli.parse_opts.base_date, chrono::NaiveDate::from_ymd_opt(2025, 9, 22));
+        assert!(parse(&["--base-date", "2025-09-22T00:00:00Z", "21:00"]).is_err());
+        assert!(parse(&["--base-date", "someday", "21:00"]).is_err());
+    }
+
+    #[test]
+    fn assume_year_takes_a_year() {
+        assert_eq!(parse(&["--assume-year", "2026", "21:00"]).unwrap().parse_opts.assume_year, Some(2026));
+        assert!(parse(&["--assume-year", "26th", "21:00"]).is_err());
+        assert!(parse(&["--assume-year", "20260", "21:00"]).is_err());
+    }
+
+    #[test]
+    fn when_phrases_split_into_from_and_to() {
+        let cli = parse(&["--when", "9:00 PM 22-09-2025 Brisbane in New York"]).unwrap();
+        assert_eq!(cli.input.as_deref(), Some("9:00 PM 22-09-2025"));
+        assert_eq!(cli.from.as_deref(), Some("Brisbane"));
+        assert_eq!(cli.to.as_deref(), Some("New York"));
+        assert!(parse(&["--when", "09:00 in Europe/London"]).is_err());
+        assert!(parse(&["--when", "09:00 Brisbane"]).is_err());
+        assert!(parse(&["--when", "09:00 Brisbane in London", "21:00"]).is_err());
+    }
+
+    #[test]
+    fn subcommands_map_onto_flags() {
+        assert_eq!(parse(&["21:00", "22-09-2025"]).unwrap().input.as_deref(), Some("21:00 22-09-2025"));
+        assert_eq!(parse(&["convert", "now"]).unwrap().input.as_deref(), Some("now"));
+        assert!(parse(&["now", "--tz", "UTC"]).unwrap().world);
+        assert_eq!(parse(&["--assume-utc", "21:00"]).unwrap().direction, Some(Direction::ToLocal));
+        let list = parse(&["list", "europe"]).unwrap();
+        assert!(list.list_timezones);
+        assert_eq!(list.list_filter.as_deref(), Some("europe"));
+        let diff = parse(&["diff", "09:00", "17:30 22-09-2025"]).unwrap();
+        assert_eq!(diff.compare, Some(("09:00".into(), "17:30 22-09-2025".into())));
+        assert!(parse(&["diff", "09:00"]).is_err());
+    }
+
+    #[test]
+    fn durations_sum_their_parts() {
+        assert_eq!(parse_duration("1h30m"), Ok(TimeDelta::minutes(90)));
+        assert_eq!(parse_duration("1d"), Ok(TimeDelta::hours(24)));
+        assert_eq!(parse_duration("45s"), Ok(TimeDelta::seconds(45)));
+        for bad in ["", "90", "h", "2x", "1h30"] {
+            assert!(parse_duration(bad).is_err(), "{bad}");
+        }
+        let cli = parse(&["--add", "2h", "--subtract", "30m", "21:00"]).unwrap();
+        assert_eq!(cli.shift, TimeDelta::minutes(90));
+    }
+}
@@ -0,0 +1,31 @@
+//! `--from-clipboard`: the clipboard's text, read through the platform's own
+//! paste command so the build needs no windowing libraries. The first
+//! command that runs and succeeds wins.
+
+use std::process::Command;
+
+#[cfg(target_os = "macos")]
+const PASTE_COMMANDS: &[&[&str]] = &[&["pbpaste"]];
+
+#[cfg(windows)]
+const PASTE_COMMANDS: &[&[&str]] = &[&["powershell", "-NoProfile", "-Command", "Get-Clipboard"]];
+
+// Wayland first, then the two common X11 tools
+#[cfg(not(any(target_os = "macos", windows)))]
+const PASTE_COMMANDS: &[&[&str]] = &[
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-o"],
+    &["xsel", "--clipboard", "--output"],
+];
+
+// The clipboard's text, which may be empty, or why none could be read
+pub fn read() -> Result<String, String> {
+    for command in PASTE_COMMANDS {
+        let Ok(output) = Command::new(command[0]).args(&command[1..]).output() else { continue };
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+    }
+    let names: Vec<&str> = PASTE_COMMANDS.iter().map(|command| command[0]).collect();
+    Err(format!("Cannot read the clipboard; tried {}", names.join(", ")))
+}
@@ -0,0 +1,99 @@
+//! Defaults read from `$XDG_CONFIG_HOME/utc_time/config`, falling back to
+//! `~/.config/utc_time/config`.
+//!
+//! The file holds `key = value` lines; blank lines and lines starting with `#`
+//! are ignored. Recognised keys are `tz` (a zone list, as for `--tz`), `format`
+//! (a `--format` name, or a strftime pattern as for `--out-format`),
+//! `choice` (`1` or `2`, as for `--choice`) and `default_choice` (`1` or `2`,
+//! what pressing Enter at the menu picks). Command-line flags override every
+//! key.
+
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use utc_time::Direction;
+
+use crate::cli::direction_from_choice;
+use crate::output::Format;
+
+#[derive(Debug, Default)]
+pub struct Config {
+    pub tz: Option<String>,
+    pub format: Option<Format>,
+    pub direction: Option<Direction>,
+    pub default_choice: Option<Direction>,
+}
+
+fn path() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("utc_time").join("config"))
+}
+
+impl Config {
+    /// A missing file gives the empty config; a malformed one is an error
+    /// naming the file and line.
+    pub fn load() -> Result<Config, String> {
+        let Some(path) = path() else { return Ok(Config::default()) };
+        match fs::read_to_string(&path) {
+            Ok(text) => Config::parse(&text).map_err(|(n, e)| format!("{}:{n}: {e}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format!("Cannot read {}: {e}", path.display())),
+        }
+    }
+
+    // Errors carry the 1-based line number
+    fn parse(text: &str) -> Result<Config, (usize, String)> {
+        let mut config = Config::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fail = |e: String| (i + 1, e);
+            let (key, value) = line
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| fail(format!("expected key = value, got '{line}'")))?;
+            match key {
+                "tz" => config.tz = Some(value.to_string()),
+                "format" if value.contains('%') => config.format = Some(Format::custom(value).map_err(fail)?),
+                "format" => config.format = Some(Format::parse(value).map_err(fail)?),
+                "choice" => {
+                    let direction = direction_from_choice(value)
+                        .ok_or_else(|| fail(format!("choice must be 1 or 2, got '{value}'")))?;
+                    config.direction = Some(direction);
+                }
+                "default_choice" => {
+                    let direction = direction_from_choice(value)
+                        .ok_or_else(|| fail(format!("default_choice must be 1 or 2, got '{value}'")))?;
+                    config.default_choice = Some(direction);
+                }
+                _ => return Err(fail(format!("unknown key '{key}'; expected tz, format, choice or default_choice"))),
+            }
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_keys_and_reports_bad_lines() {
+        let config = Config::parse("# defaults\n\ntz = Asia/Tokyo\nformat = %H:%M\nchoice = 2\n").unwrap();
+        assert_eq!(config.tz.as_deref(), Some("Asia/Tokyo"));
+        assert_eq!(config.format, Some(Format::Custom("%H:%M".into())));
+        assert_eq!(config.direction, Some(Direction::ToLocal));
+        assert_eq!(config.default_choice, None);
+        assert_eq!(Config::parse("default_choice = 1").unwrap().default_choice, Some(Direction::ToUtc));
+        assert_eq!(Config::parse("tz = UTC\nchoice = 3").unwrap_err().0, 2);
+        assert!(Config::parse("default_choice =").is_err());
+        assert!(Config::parse("timezone").is_err());
+    }
+}
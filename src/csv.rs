@@ -0,0 +1,49 @@
+//! Just enough CSV for `--csv`: comma-separated fields on one line, with
+//! double-quoted fields that may hold commas and `""` for a quote. Fields
+//! spanning lines are not supported.
+
+// Splits one record into its unquoted fields
+pub fn split_record(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().expect("never empty");
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => fields.push(String::new()),
+            _ => field.push(c),
+        }
+    }
+    fields
+}
+
+// The inverse of `split_record`, quoting only the fields that need it
+pub fn join_record(fields: &[String]) -> String {
+    let quote = |field: &String| {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.clone()
+        }
+    };
+    fields.iter().map(quote).collect::<Vec<_>>().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quoted_fields_keep_their_commas() {
+        let fields = split_record(r#"7,"21:00 22-09-2025","say ""hi"", then go",,end"#);
+        assert_eq!(fields, ["7", "21:00 22-09-2025", r#"say "hi", then go"#, "", "end"]);
+        assert_eq!(join_record(&fields), r#"7,21:00 22-09-2025,"say ""hi"", then go",,end"#);
+        assert_eq!(split_record(""), [""]);
+    }
+}
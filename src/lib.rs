@@ -0,0 +1,2222 @@
+//! Parsing helpers behind the `utc_time` binary.
+//!
+//! Times are read as naive wall-clock values; deciding which zone they belong
+//! to is left to the caller. Epoch timestamps, RFC3339 strings and `now` are the
+//! exception: they already name an instant and come back as [`Input::Instant`].
+//!
+//! The off-by-default `serde` feature derives `Serialize` and `Deserialize`
+//! for [`ParseError`], [`Conversion`] and [`Direction`].
+
+pub mod abbrev;
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, BufRead};
+
+use chrono::{DateTime, Days, Weekday, FixedOffset, LocalResult, NaiveDateTime, NaiveDate, NaiveTime, Datelike, Month, Offset, SubsecRound, TimeDelta, TimeZone, Utc};
+use chrono_tz::Australia::Brisbane;
+use chrono_tz::Tz;
+
+/// The result of [`parse`]: either a wall-clock time still waiting for a zone,
+/// or an instant that needs no further interpretation. Instants keep the
+/// offset they were written with; epochs and `now` carry `+00:00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Naive(NaiveDateTime),
+    Instant(DateTime<FixedOffset>),
+}
+
+impl Input {
+    /// The wall-clock value; for an instant this is its UTC wall-clock.
+    pub fn naive(self) -> NaiveDateTime {
+        match self {
+            Input::Naive(ndt) => ndt,
+            Input::Instant(dt) => dt.naive_utc(),
+        }
+    }
+}
+
+/// How [`parse_traced`] arrived at its result, for diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trace {
+    /// The strftime pattern that matched, or a name such as `epoch` or
+    /// `rfc3339` for forms not parsed by pattern.
+    pub format: &'static str,
+    /// The value as first parsed, before any two-digit year was rewritten.
+    pub parsed: NaiveDateTime,
+    /// `(from, to)` when [`fix_two_digit_year`] changed the year.
+    pub year_rewrite: Option<(i32, i32)>,
+    /// The `yy-mm-dd` reading of a day-first date such as `22-09-12`, when
+    /// that is a real date too and differs from the one chosen.
+    pub year_first: Option<NaiveDate>,
+}
+
+/// The default cutoff for [`map_two_digit_year`]: `00..=68` are 2000s.
+pub const DEFAULT_YEAR_PIVOT: i32 = 68;
+
+/// The scale of a numeric epoch input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EpochUnit {
+    #[default]
+    Seconds,
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl EpochUnit {
+    fn nanos(self) -> i128 {
+        match self {
+            EpochUnit::Seconds => 1_000_000_000,
+            EpochUnit::Millis => 1_000_000,
+            EpochUnit::Micros => 1_000,
+            EpochUnit::Nanos => 1,
+        }
+    }
+}
+
+impl std::str::FromStr for EpochUnit {
+    type Err = String;
+
+    /// Accepts `s`, `ms`, `us` or `ns`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "s" => Ok(EpochUnit::Seconds),
+            "ms" => Ok(EpochUnit::Millis),
+            "us" => Ok(EpochUnit::Micros),
+            "ns" => Ok(EpochUnit::Nanos),
+            _ => Err(format!("Unknown epoch unit '{s}'; expected s, ms, us or ns")),
+        }
+    }
+}
+
+/// Where a numeric epoch count starts, and for FILETIME what it counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EpochReference {
+    /// 1970-01-01T00:00:00Z, counted in [`ParseOptions::epoch_unit`].
+    #[default]
+    Unix,
+    /// 2000-01-01T00:00:00Z, 946684800 Unix seconds, counted in
+    /// [`ParseOptions::epoch_unit`]. This is midnight UTC, not the
+    /// astronomers' noon TT.
+    J2000,
+    /// 1601-01-01T00:00:00Z, -11644473600 Unix seconds, counted in the
+    /// 100-nanosecond ticks of a Windows FILETIME whatever the epoch unit.
+    FileTime,
+}
+
+impl EpochReference {
+    /// Unix seconds at this reference's zero.
+    pub fn unix_offset(self) -> i64 {
+        match self {
+            EpochReference::Unix => 0,
+            EpochReference::J2000 => 946_684_800,
+            EpochReference::FileTime => -11_644_473_600,
+        }
+    }
+
+    // Nanoseconds in one count of `unit` from this reference
+    fn tick_nanos(self, unit: EpochUnit) -> i128 {
+        match self {
+            EpochReference::FileTime => 100,
+            EpochReference::Unix | EpochReference::J2000 => unit.nanos(),
+        }
+    }
+
+    /// Whole seconds, or FILETIME ticks, from this reference to `utc`; the
+    /// inverse of how epoch input is read. Rounds towards the past.
+    pub fn count(self, utc: DateTime<Utc>) -> i128 {
+        let nanos = (i128::from(utc.timestamp()) - i128::from(self.unix_offset())) * 1_000_000_000
+            + i128::from(utc.timestamp_subsec_nanos());
+        nanos.div_euclid(self.tick_nanos(EpochUnit::Seconds))
+    }
+}
+
+impl std::str::FromStr for EpochReference {
+    type Err = String;
+
+    /// Accepts `unix`, `j2000` or `filetime`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unix" => Ok(EpochReference::Unix),
+            "j2000" => Ok(EpochReference::J2000),
+            "filetime" => Ok(EpochReference::FileTime),
+            _ => Err(format!("Unknown epoch reference '{s}'; expected unix, j2000 or filetime")),
+        }
+    }
+}
+
+/// Why [`parse`] rejected its input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseError {
+    /// The input is empty or only whitespace.
+    Empty,
+    /// No supported format matched.
+    UnrecognizedFormat,
+    /// The part before a relative day word is not a time.
+    UnrecognizedTime { time: String, word: String },
+    /// A relative day word such as `tomorrow` on its own.
+    MissingTime(String),
+    /// An epoch timestamp beyond the representable range.
+    EpochOutOfRange(String),
+    /// A 24-hour hour above 23.
+    InvalidHour(u32),
+    /// A 12-hour hour outside `1..=12`.
+    InvalidHour12(u32),
+    InvalidMinute(u32),
+    InvalidSecond(u32),
+    /// A numeric date whose day does not exist in its month.
+    InvalidDate { day: u32, month: u32, year: i32 },
+    /// A zone abbreviation in the input that several zones use.
+    AmbiguousZone { abbr: String, candidates: Vec<String> },
+    /// An ISO week date naming a week the year lacks, or a day outside `1..=7`.
+    InvalidWeekDate { year: i32, week: u32, day: u32 },
+    /// A numeric offset word such as `+25:00` beyond ±23:59.
+    InvalidOffset(String),
+    /// A two-digit year under [`ParseOptions::strict_years`].
+    TwoDigitYear(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => f.write_str("no time provided"),
+            ParseError::UnrecognizedFormat => f.write_str(
+                "Unrecognized format. Try: HH:MM[:SS] or HH:MM[:SS] yyyy-mm-dd or HH:MM[:SS] dd-mm-yy|yyyy or HH:MM[:SS] dd/mm/yy|yyyy (date first also works) or @EPOCH or RFC3339",
+            ),
+            ParseError::UnrecognizedTime { time, word } => write!(f, "Unrecognized time before '{word}': {time}"),
+            ParseError::MissingTime(word) => write!(f, "'{word}' needs a time, e.g. 21:00 {word}"),
+            ParseError::EpochOutOfRange(s) => write!(f, "Epoch timestamp out of range: {s}"),
+            ParseError::InvalidHour(hour) => write!(f, "hour must be 0–23, got {hour}"),
+            ParseError::InvalidHour12(hour) => write!(f, "hour must be 1–12 with AM/PM, got {hour}"),
+            ParseError::InvalidMinute(minute) => write!(f, "minute must be 0–59, got {minute}"),
+            ParseError::InvalidSecond(second) => write!(f, "second must be 0–59, or 60 for a leap second, got {second}"),
+            ParseError::AmbiguousZone { abbr, candidates } => {
+                write!(f, "'{abbr}' could mean {}; write an offset or use --tz instead", candidates.join(", "))
+            }
+            ParseError::InvalidWeekDate { year, week, day } if !(1..=7).contains(day) => {
+                write!(f, "ISO weekday must be 1–7 (Monday–Sunday), got {day} in {year}-W{week:02}")
+            }
+            ParseError::InvalidWeekDate { year, week, .. } => write!(f, "{year} has no ISO week {week}"),
+            ParseError::InvalidOffset(word) => write!(f, "offset must be within ±23:59, got {word}"),
+            ParseError::TwoDigitYear(year) => write!(f, "year '{year}' has two digits; write the full year"),
+            ParseError::InvalidDate { day, month, year } => {
+                let name = u8::try_from(*month)
+                    .ok()
+                    .and_then(|m| Month::try_from(m).ok())
+                    .map_or_else(|| format!("Month {month}"), |m| m.name().to_string());
+                if *month == 2 && *day == 29 {
+                    write!(f, "{name} {year} has no day 29 ({year} is not a leap year)")
+                } else {
+                    write!(f, "{name} has no day {day}")
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Knobs for [`parse_traced`]. The defaults match [`parse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Two-digit years up to and including this map to 2000s, later ones to
+    /// 1900s.
+    pub year_pivot: i32,
+    /// Resolve weekday names to the most recent past occurrence instead of
+    /// the next one.
+    pub past_weekdays: bool,
+    /// How to scale numeric epoch input.
+    pub epoch_unit: EpochUnit,
+    /// Where numeric epoch input counts from.
+    pub epoch_reference: EpochReference,
+    /// Whose calendar gives "today" to a bare time and to `today`,
+    /// `tomorrow`, `yesterday` and weekday names.
+    pub today_zone: Tz,
+    /// A fixed "today" for those same inputs, in place of the clock and
+    /// `today_zone`. Dates written in the input are unaffected.
+    pub base_date: Option<NaiveDate>,
+    /// The year for a bare time's date, keeping the month and day of today
+    /// (or of `base_date`, whose year it replaces). Relative days and dates
+    /// written in the input are unaffected.
+    pub assume_year: Option<i32>,
+    /// Reject two-digit years with [`ParseError::TwoDigitYear`] instead of
+    /// placing them by `year_pivot`.
+    pub strict_years: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            year_pivot: DEFAULT_YEAR_PIVOT,
+            past_weekdays: false,
+            epoch_unit: EpochUnit::Seconds,
+            epoch_reference: EpochReference::Unix,
+            today_zone: Brisbane,
+            base_date: None,
+            assume_year: None,
+            strict_years: false,
+        }
+    }
+}
+
+/// Maps a two-digit year onto a full year: `0..=pivot` become 2000s and
+/// `pivot+1..=99` become 1900s. Anything else is returned unchanged.
+pub fn map_two_digit_year(y: i32, pivot: i32) -> i32 {
+    if (0..=pivot).contains(&y) { 2000 + y }
+    else if (pivot + 1..=99).contains(&y) { 1900 + y }
+    else { y }
+}
+
+/// Rewrites the year of `ndt` with [`map_two_digit_year`] if it was parsed
+/// from a two-digit `%y` field (i.e. falls in `0..=99`).
+pub fn fix_two_digit_year(ndt: NaiveDateTime, pivot: i32) -> NaiveDateTime {
+    let y = ndt.date().year();
+    if (0..=99).contains(&y) {
+        // Leap years line up between `y` and its mapped year, so this only
+        // falls back for out-of-range pivots
+        ndt.with_year(map_two_digit_year(y, pivot)).unwrap_or(ndt)
+    } else {
+        ndt
+    }
+}
+
+/// The 19xx and 20xx readings of `ndt`, earliest first, for when a
+/// two-digit year should not be pinned to one century. Either side may be
+/// missing: 29 February exists in 2000 but not in 1900.
+pub fn century_candidates(ndt: NaiveDateTime) -> Vec<NaiveDateTime> {
+    let yy = ndt.year().rem_euclid(100);
+    [1900, 2000].into_iter().filter_map(|century| ndt.with_year(century + yy)).collect()
+}
+
+// Formats for a time on its own, 24-hour before 12-hour. `%.f` is optional,
+// so every seconds pattern in this file also takes a fraction
+const TIME_FORMATS: [Pattern; 4] = [
+    pattern("%H:%M:%S%.f"),
+    pattern("%H:%M"),
+    pattern("%I:%M:%S%.f %p"),
+    pattern("%I:%M %p"),
+];
+
+fn parse_time(s: &str) -> Option<(NaiveTime, &'static str)> {
+    Shape::of(s)
+        .candidates(TIME_FORMATS)
+        .find_map(|fmt| NaiveTime::parse_from_str(s, fmt).ok().map(|t| (t, fmt)))
+}
+
+fn today_in(zone: Tz) -> NaiveDate {
+    Utc::now().with_timezone(&zone).date_naive()
+}
+
+// "Today" as `opts` sees it
+fn today(opts: &ParseOptions) -> NaiveDate {
+    opts.base_date.unwrap_or_else(|| today_in(opts.today_zone))
+}
+
+// The date a bare time falls on: today, moved into `opts.assume_year` if set.
+// 29 February has no such day in most years
+fn bare_time_date(opts: &ParseOptions) -> Result<NaiveDate, ParseError> {
+    let today = today(opts);
+    match opts.assume_year {
+        Some(year) => today.with_year(year).ok_or(ParseError::InvalidDate { day: today.day(), month: today.month(), year }),
+        None => Ok(today),
+    }
+}
+
+// `today`, `tomorrow`, `yesterday` or a weekday name, relative to today in
+// [`ParseOptions::today_zone`] or to [`ParseOptions::base_date`]
+fn relative_day(word: &str, opts: &ParseOptions) -> Option<NaiveDate> {
+    // Checked before asking the clock, since most inputs name no day at all
+    let word = word.to_ascii_lowercase();
+    let weekday = match word.as_str() {
+        "today" | "tomorrow" | "yesterday" => None,
+        name => Some(name.parse::<Weekday>().ok()?),
+    };
+    let today = today(opts);
+    match (word.as_str(), weekday) {
+        (_, Some(target)) => {
+            // Never today itself: a week ahead (or back) when the names match
+            let gap = |from: Weekday, to: Weekday| match to.days_since(from) {
+                0 => 7,
+                n => u64::from(n),
+            };
+            if opts.past_weekdays {
+                today.checked_sub_days(Days::new(gap(target, today.weekday())))
+            } else {
+                today.checked_add_days(Days::new(gap(today.weekday(), target)))
+            }
+        }
+        ("tomorrow", None) => today.checked_add_days(Days::new(1)),
+        ("yesterday", None) => today.checked_sub_days(Days::new(1)),
+        _ => Some(today),
+    }
+}
+
+// `[-]digits[.digits]`
+fn is_epoch(s: &str) -> bool {
+    let unsigned = s.strip_prefix('-').unwrap_or(s);
+    let (int, frac) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    !int.is_empty() && int.bytes().chain(frac.bytes()).all(|b| b.is_ascii_digit())
+}
+
+// Works in whole nanoseconds so every unit and fraction shares one overflow check
+fn parse_epoch(s: &str, unit: EpochUnit, reference: EpochReference) -> Option<DateTime<Utc>> {
+    let (negative, unsigned) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let tick = reference.tick_nanos(unit);
+    let (int, frac) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let whole = int.parse::<i128>().ok()?.checked_mul(tick)?;
+    // Fractions finer than a nanosecond are dropped
+    let frac_nanos = format!("{frac:0<9}")[..9].parse::<i128>().ok()? * tick / 1_000_000_000;
+    let total = whole.checked_add(frac_nanos)?;
+    let total = if negative { -total } else { total };
+    let total = total.checked_add(i128::from(reference.unix_offset()) * 1_000_000_000)?;
+    let secs = i64::try_from(total.div_euclid(1_000_000_000)).ok()?;
+    let nanos = total.rem_euclid(1_000_000_000) as u32;
+    DateTime::from_timestamp(secs, nanos)
+}
+
+// True when `date` looks like `yyyy-...`
+fn leads_with_year(date: Option<&str>) -> bool {
+    date.and_then(|date| date.split('-').next())
+        .is_some_and(|y| y.len() == 4 && y.bytes().all(|b| b.is_ascii_digit()))
+}
+
+// Splits off an upper-case zone abbreviation such as the `AEST` in
+// `21:00 AEST 22-09-2025`, returning it and the remaining words
+fn split_zone_word(s: &str) -> Option<(&str, String)> {
+    let words: Vec<&str> = s.split(' ').collect();
+    let i = words.iter().position(|w| {
+        (2..=5).contains(&w.len())
+            && w.bytes().all(|b| b.is_ascii_uppercase())
+            && !matches!(*w, "AM" | "PM")
+            && !abbrev::zones_for(w).is_empty()
+    })?;
+    let rest: Vec<&str> = words.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, w)| *w).collect();
+    Some((words[i], rest.join(" ")))
+}
+
+// Splits off a numeric offset word such as the `+10:00` in
+// `21:00 +10:00 22-09-2025`; `±HH`, `±HHMM` and `±HH:MM` are accepted
+fn split_offset_word(s: &str) -> Option<(Result<FixedOffset, ParseError>, String)> {
+    let words: Vec<&str> = s.split(' ').collect();
+    let digits = |f: &str| f.len() == 2 && f.bytes().all(|b| b.is_ascii_digit());
+    let fields = |w: &str| {
+        let (sign, rest) = (w.chars().next()?, w.get(1..)?);
+        let (hours, minutes) = match rest.split_once(':') {
+            Some(parts) => parts,
+            None if rest.len() == 4 => rest.split_at(2),
+            None => (rest, "00"),
+        };
+        let sign = match sign {
+            '+' => 1,
+            '-' => -1,
+            _ => return None,
+        };
+        if !(digits(hours) && digits(minutes)) {
+            return None;
+        }
+        Some((sign, hours.parse::<i32>().ok()?, minutes.parse::<i32>().ok()?))
+    };
+    let i = words.iter().position(|w| fields(w).is_some())?;
+    let offset = match fields(words[i])? {
+        (sign, h @ 0..=23, m @ 0..=59) => FixedOffset::east_opt(sign * (h * 3600 + m * 60)),
+        _ => None,
+    };
+    let rest: Vec<&str> = words.iter().enumerate().filter(|&(j, _)| j != i).map(|(_, w)| *w).collect();
+    let offset = offset.ok_or_else(|| ParseError::InvalidOffset(words[i].to_string()));
+    Some((offset, rest.join(" ")))
+}
+
+// The offset of a military zone letter: `A`-`I` and `K`-`M` are +1 to +12
+// (`J` is the observer's local time, so it has none), `N`-`Y` are -1 to -12
+// and `Z` is UTC
+fn military_offset(letter: char) -> Option<FixedOffset> {
+    let hours = match letter {
+        'A'..='I' => letter as i32 - 'A' as i32 + 1,
+        'K'..='M' => letter as i32 - 'K' as i32 + 10,
+        'N'..='Y' => -(letter as i32 - 'N' as i32 + 1),
+        'Z' => 0,
+        _ => return None,
+    };
+    FixedOffset::east_opt(hours * 3600)
+}
+
+// Splits the military zone letter off a time such as the `K` in
+// `21:00K 22-09-2025`, leaving `21:00 22-09-2025`
+fn split_military_zone(s: &str) -> Option<(FixedOffset, String)> {
+    let mut words: Vec<&str> = s.split(' ').collect();
+    let (i, offset) = words.iter().enumerate().find_map(|(i, w)| {
+        let time = w.strip_suffix(|c: char| c.is_ascii_uppercase())?;
+        parse_time(time)?;
+        Some((i, military_offset(w.chars().last()?)?))
+    })?;
+    words[i] = &words[i][..words[i].len() - 1];
+    Some((offset, words.join(" ")))
+}
+
+// `yyyy-Www-d`; the week must exist in that ISO year
+fn iso_week_date(word: &str) -> Option<Result<NaiveDate, ParseError>> {
+    let (year, rest) = word.split_once('-')?;
+    let (week, day) = rest.strip_prefix(['W', 'w'])?.split_once('-')?;
+    let digits = |f: &str, len: usize| f.len() == len && f.bytes().all(|b| b.is_ascii_digit());
+    if !(digits(year, 4) && digits(week, 2) && digits(day, 1)) {
+        return None;
+    }
+    let (year, week, day) = (year.parse().ok()?, week.parse().ok()?, day.parse::<u8>().ok()?);
+    let weekday = match day {
+        1..=7 => Weekday::try_from(day - 1).ok()?,
+        _ => return Some(Err(ParseError::InvalidWeekDate { year, week, day: day.into() })),
+    };
+    Some(NaiveDate::from_isoywd_opt(year, week, weekday).ok_or(ParseError::InvalidWeekDate {
+        year,
+        week,
+        day: day.into(),
+    }))
+}
+
+// The separators a string holds, and whether it has a T or M that an ISO
+// form or AM/PM would need. Comparing shapes is far cheaper than a failed
+// parse, so patterns that could only fail are skipped rather than tried
+#[derive(Clone, Copy)]
+struct Shape {
+    dashes: u8,
+    slashes: u8,
+    colons: u8,
+    t: bool,
+    m: bool,
+    // Only meaningful for a pattern: whether it has `%Y` and `%p`
+    year: bool,
+    ampm: bool,
+}
+
+impl Shape {
+    const fn of(text: &str) -> Shape {
+        let mut shape = Shape { dashes: 0, slashes: 0, colons: 0, t: false, m: false, year: false, ampm: false };
+        let bytes = text.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'-' => shape.dashes = shape.dashes.saturating_add(1),
+                b'/' => shape.slashes = shape.slashes.saturating_add(1),
+                b':' => shape.colons = shape.colons.saturating_add(1),
+                b'T' | b't' => shape.t = true,
+                b'M' | b'm' => shape.m = true,
+                b'%' if i + 1 < bytes.len() => {
+                    i += 1;
+                    match bytes[i] {
+                        b'Y' => shape.year = true,
+                        b'p' => shape.ampm = true,
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        shape
+    }
+
+    // No specifier used here reads a `/` or `:`, so those counts must agree;
+    // `%Y` may take a sign, so the input may have an extra `-` there
+    fn fits(self, want: Shape) -> bool {
+        let dashes = if want.year { self.dashes >= want.dashes } else { self.dashes == want.dashes };
+        dashes
+            && self.slashes == want.slashes
+            && self.colons == want.colons
+            && (!want.t || self.t)
+            && (!want.ampm || self.m)
+    }
+
+    // The patterns, in order, that input of this shape could match
+    fn candidates<const N: usize>(self, patterns: [Pattern; N]) -> impl Iterator<Item = &'static str> {
+        patterns.into_iter().filter(move |p| self.fits(p.shape)).map(|p| p.fmt)
+    }
+}
+
+// A strftime pattern with its shape worked out at compile time
+#[derive(Clone, Copy)]
+struct Pattern {
+    fmt: &'static str,
+    shape: Shape,
+}
+
+const fn pattern(fmt: &'static str) -> Pattern {
+    Pattern { fmt, shape: Shape::of(fmt) }
+}
+
+// Time first with an ISO date; see `parse_traced` for when each group is tried
+const ISO_TIME_FIRST: [Pattern; 2] = [pattern("%H:%M:%S%.f %Y-%m-%d"), pattern("%H:%M %Y-%m-%d")];
+
+// Time first, then the day-first date. Within each time form the dash and
+// slash spellings cannot both match, so their order is free; seconds come
+// before none only to try the likelier form first, and 24-hour before
+// 12-hour so a bare `9:00` never waits on AM/PM. There is no `%y`: `%Y`
+// takes two digits too and the pivot is applied afterwards, which `%y`,
+// always splitting at 70, could not honour. So `01-02-03` is 1 Feb 2003
+const FORMATS: [Pattern; 8] = [
+    pattern("%H:%M:%S%.f %d-%m-%Y"), // 21:00:30 22-09-2025, 21:00:30 22-09-25
+    pattern("%H:%M:%S%.f %d/%m/%Y"), // 21:00:30 22/09/2025
+    pattern("%H:%M %d-%m-%Y"), // 21:00 22-09-2025
+    pattern("%H:%M %d/%m/%Y"), // 21:00 22/09/25
+    pattern("%I:%M:%S%.f %p %d-%m-%Y"), // 9:00:30 PM 22-09-2025
+    pattern("%I:%M:%S%.f %p %d/%m/%Y"), // 9:00:30 PM 22/09/2025
+    pattern("%I:%M %p %d-%m-%Y"), // 9:00 PM 22-09-2025
+    pattern("%I:%M %p %d/%m/%Y"), // 9:00 PM 22/09/25
+];
+
+// The ISO date before the time
+const ISO_DATE_FIRST: [Pattern; 2] = [pattern("%Y-%m-%d %H:%M:%S%.f"), pattern("%Y-%m-%d %H:%M")];
+
+// The day-first dates written before the time
+const DATE_FIRST: [Pattern; 8] = [
+    pattern("%d-%m-%Y %H:%M:%S%.f"), // 22-09-2025 21:00:30
+    pattern("%d/%m/%Y %H:%M:%S%.f"), // 22/09/2025 21:00:30
+    pattern("%d-%m-%Y %H:%M"), // 22-09-2025 21:00
+    pattern("%d/%m/%Y %H:%M"), // 22/09/2025 21:00
+    pattern("%d-%m-%Y %I:%M:%S%.f %p"), // 22-09-2025 9:00:30 PM
+    pattern("%d/%m/%Y %I:%M:%S%.f %p"), // 22/09/2025 9:00:30 PM
+    pattern("%d-%m-%Y %I:%M %p"), // 22-09-2025 9:00 PM
+    pattern("%d/%m/%Y %I:%M %p"), // 22/09/2025 9:00 PM
+];
+
+// ISO 8601's basic format, the compact `20250922T210000` of `--format ical`
+// without its `Z`. The `T` may be left out, making fourteen digits
+fn iso_basic(s: &str) -> Option<(NaiveDateTime, &'static str)> {
+    let (date, time, fmt) = match s.split_once(['T', 't']) {
+        Some((date, time)) => (date, time, "%Y%m%dT%H%M%S"),
+        None if s.len() == 14 => (&s[..8], &s[8..], "%Y%m%d%H%M%S"),
+        None => return None,
+    };
+    if date.len() != 8 || time.len() != 6 || !date.bytes().chain(time.bytes()).all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let field = |text: &str, at: usize, len: usize| text[at..at + len].parse::<u32>().ok();
+    let date = NaiveDate::from_ymd_opt(date[..4].parse().ok()?, field(date, 4, 2)?, field(date, 6, 2)?)?;
+    Some((date.and_hms_opt(field(time, 0, 2)?, field(time, 2, 2)?, field(time, 4, 2)?)?, fmt))
+}
+
+// An ISO week date with a time on either side, e.g. `2025-W39-1 21:00`
+fn iso_week_datetime(s: &str) -> Result<Option<(NaiveDateTime, &'static str)>, ParseError> {
+    let Some((first, second)) = s.split_once(' ') else { return Ok(None) };
+    let (date, time) = match (iso_week_date(first), iso_week_date(second)) {
+        (Some(date), _) => (date?, second),
+        (None, Some(date)) => (date?, first),
+        (None, None) => return Ok(None),
+    };
+    let (t, fmt) = parse_time(time).ok_or_else(|| impossible_time(time).unwrap_or(ParseError::UnrecognizedFormat))?;
+    Ok(Some((NaiveDateTime::new(date, t), fmt)))
+}
+
+// Explains a numeric time with a field out of range, e.g. `25:00`, which
+// every pattern would otherwise reject without saying why
+fn impossible_time(s: &str) -> Option<ParseError> {
+    let mut words = s.split(' ');
+    let time = words.find(|w| w.contains(':'))?;
+    let clock12 = words.next().is_some_and(|w| w.eq_ignore_ascii_case("am") || w.eq_ignore_ascii_case("pm"));
+    let whole = time.split_once('.').map_or(time, |(whole, _)| whole);
+    let fields: Vec<u32> = whole
+        .split(':')
+        .map(|f| Some(f).filter(|f| (1..=2).contains(&f.len()))?.parse().ok())
+        .collect::<Option<_>>()?;
+    let (hour, rest) = match fields[..] {
+        [h, m] => (h, [Some(m), None]),
+        [h, m, sec] => (h, [Some(m), Some(sec)]),
+        _ => return None,
+    };
+    if clock12 && !(1..=12).contains(&hour) {
+        return Some(ParseError::InvalidHour12(hour));
+    }
+    if hour > 23 {
+        return Some(ParseError::InvalidHour(hour));
+    }
+    match rest {
+        [Some(m), _] if m > 59 => Some(ParseError::InvalidMinute(m)),
+        [_, Some(sec)] if sec > 60 => Some(ParseError::InvalidSecond(sec)),
+        _ => None,
+    }
+}
+
+// Explains a numeric date whose day doesn't exist in its month, e.g.
+// `31-02-2025`, which every pattern would otherwise reject without saying why
+fn impossible_day(s: &str, pivot: i32) -> Option<ParseError> {
+    let date = s.split(' ').find(|w| w.contains(['-', '/']))?;
+    let fields: Vec<&str> = date.split(['-', '/']).collect();
+    let [a, b, c] = fields[..] else { return None };
+    let (y, m, d) = if a.len() == 4 { (a, b, c) } else { (c, b, a) };
+    // Longer years never parse, so there is no day to blame
+    if y.len() > 4 {
+        return None;
+    }
+    let (mut year, month, day) = (y.parse::<i32>().ok()?, m.parse::<u32>().ok()?, d.parse::<u32>().ok()?);
+    if y.len() <= 2 {
+        year = map_two_digit_year(year, pivot);
+    }
+    // Only months that exist, so a bad month stays an unrecognized format
+    if day == 0 || !(1..=12).contains(&month) || NaiveDate::from_ymd_opt(year, month, day).is_some() {
+        return None;
+    }
+    Some(ParseError::InvalidDate { day, month, year })
+}
+
+// `22-09-12` also reads as 2022-09-12 when taken year first; only dates whose
+// fields are all two digits wide are open to that mix-up
+fn year_first_reading(s: &str, pivot: i32) -> Option<NaiveDate> {
+    let date = s.split(' ').find(|w| w.contains(['-', '/']))?;
+    let fields: Vec<&str> = date.split(['-', '/']).collect();
+    let [y, m, d] = fields[..] else { return None };
+    if [y, m, d].iter().any(|f| f.len() != 2) {
+        return None;
+    }
+    let year = map_two_digit_year(y.parse().ok()?, pivot);
+    NaiveDate::from_ymd_opt(year, m.parse().ok()?, d.parse().ok()?)
+}
+
+/// Splits a range such as `21:00-23:00 22-09-2025` into two inputs that
+/// share the date, here `21:00 22-09-2025` and `23:00 22-09-2025`. The date
+/// may be omitted. Returns `None` when `s` does not start with `TIME-TIME`.
+///
+/// ```
+/// use utc_time::split_range;
+///
+/// let (start, end) = split_range("21:00-23:00 22-09-2025").unwrap();
+/// assert_eq!(start, "21:00 22-09-2025");
+/// assert_eq!(end, "23:00 22-09-2025");
+/// ```
+pub fn split_range(s: &str) -> Option<(String, String)> {
+    let s = &*tidy_whitespace(s);
+    let (times, rest) = match s.split_once(' ') {
+        Some((times, rest)) => (times, Some(rest)),
+        None => (s, None),
+    };
+    let (start, end) = times.split_once('-')?;
+    if parse_time(start).is_none() || parse_time(end).is_none() {
+        return None;
+    }
+    Some(match rest {
+        Some(date) => (format!("{start} {date}"), format!("{end} {date}")),
+        None => (start.to_string(), end.to_string()),
+    })
+}
+
+// Collapses runs of whitespace, tabs included, to one space and trims the
+// ends, so pasted input matches the single-space patterns. Borrows when `s`
+// is already tidy
+fn tidy_whitespace(s: &str) -> Cow<'_, str> {
+    let messy = s.starts_with(char::is_whitespace)
+        || s.ends_with(char::is_whitespace)
+        || s.contains("  ")
+        || s.contains(|c: char| c.is_whitespace() && c != ' ');
+    if messy { Cow::Owned(s.split_whitespace().collect::<Vec<_>>().join(" ")) } else { Cow::Borrowed(s) }
+}
+
+// `noon` and `midnight` stand for the time they name, so `noon 22-09-2025`
+// reads as `12:00 22-09-2025`. Borrows when neither word is there
+fn tidy_noon(s: &str) -> Cow<'_, str> {
+    let time = |w: &str| match w.to_ascii_lowercase().as_str() {
+        "noon" => Some("12:00"),
+        "midnight" => Some("00:00"),
+        _ => None,
+    };
+    if !s.split(' ').any(|w| time(w).is_some()) {
+        return Cow::Borrowed(s);
+    }
+    Cow::Owned(s.split(' ').map(|w| time(w).unwrap_or(w)).collect::<Vec<_>>().join(" "))
+}
+
+// Spells AM/PM the one way the patterns expect: `9:00PM` gains its space and
+// `p.m.` loses its periods. Borrows when there is nothing to change
+fn tidy_meridiem(s: &str) -> Cow<'_, str> {
+    // A word's time part, empty for a word on its own, and its AM or PM
+    fn split(w: &str) -> Option<(&str, &'static str)> {
+        let (time, suffix) = w.split_at(w.find(|c: char| c.is_ascii_alphabetic())?);
+        let meridiem = match suffix.to_ascii_lowercase().as_str() {
+            "am" | "a.m." => "AM",
+            "pm" | "p.m." => "PM",
+            _ => return None,
+        };
+        let glued = time.contains(':') && time.ends_with(|c: char| c.is_ascii_digit());
+        (time.is_empty() || glued).then_some((time, meridiem))
+    }
+    // A spaced `pm` already matches, since %p ignores case
+    let untidy = |w: &str| split(w).is_some_and(|(time, _)| !time.is_empty() || w.contains('.'));
+    if !s.split(' ').any(untidy) {
+        return Cow::Borrowed(s);
+    }
+    let words: Vec<String> = s
+        .split(' ')
+        .map(|w| match split(w) {
+            Some(("", meridiem)) => meridiem.to_string(),
+            Some((time, meridiem)) => format!("{time} {meridiem}"),
+            None => w.to_string(),
+        })
+        .collect();
+    Cow::Owned(words.join(" "))
+}
+
+/// Like [`parse`], but flattens the result to a `NaiveDateTime`. Epoch input
+/// comes back as its UTC wall-clock.
+///
+/// ```
+/// use chrono::{NaiveDate, NaiveDateTime};
+/// use utc_time::parse_input;
+///
+/// let ndt = parse_input("21:00 22-09-2025").unwrap();
+/// let expected: NaiveDateTime = NaiveDate::from_ymd_opt(2025, 9, 22)
+///     .unwrap()
+///     .and_hms_opt(21, 0, 0)
+///     .unwrap();
+/// assert_eq!(ndt, expected);
+/// ```
+pub fn parse_input(s: &str) -> Result<NaiveDateTime, ParseError> {
+    parse(s).map(Input::naive)
+}
+
+/// Parses `HH:MM[:SS]`, or `HH:MM[:SS]` followed by a `yyyy-mm-dd`,
+/// `dd-mm-yy|yyyy` or `dd/mm/yy|yyyy` date. A bare time is placed on today's
+/// Brisbane date (see [`ParseOptions::today_zone`]) and missing seconds
+/// default to zero. Runs of spaces and tabs count as one space, and the ends
+/// are trimmed. Seconds may carry a fraction, as in `21:00:30.25`, to
+/// nanosecond precision.
+///
+/// An ISO week date `yyyy-Www-d` (`d` is 1 for Monday to 7 for Sunday) may
+/// stand in for the date on either side of the time, as in `2025-W39-1 21:00`.
+///
+/// An upper-case zone abbreviation anywhere among the words, as in
+/// `21:00 AEST 22-09-2025`, makes the result an [`Input::Instant`] at the
+/// offset the abbreviation stands for (see [`abbrev::offset_for`]). A numeric
+/// offset word, as in `21:00 +10:00 22-09-2025` (also `+1000` or `+10`), does
+/// the same with that offset, so no zone needs naming.
+///
+/// The date may also come first, as in `22-09-2025 21:00` or
+/// `2025-09-22 21:00`; these spellings are tried after all time-first ones.
+/// A date with no time at all, such as `22-09-2025`, means its midnight.
+///
+/// 12-hour times such as `9:00 PM` (or `pm`, `9:00PM` or `9:00 p.m.`) are
+/// accepted in place of `HH:MM` and are tried after the 24-hour forms. `12:00 AM` is midnight and
+/// `12:00 PM` is noon. The words `noon` and `midnight` may stand in for the
+/// time too, as in `noon 22-09-2025`; `midnight tomorrow` is the 00:00 that
+/// starts tomorrow.
+///
+/// A time may also be followed by `today`, `tomorrow` or `yesterday`, counted
+/// from today's Brisbane date, or by a weekday name (`Friday` or `fri`), which
+/// means the next such day after today, or the last one before it with
+/// [`ParseOptions::past_weekdays`].
+///
+/// A bare number, optionally prefixed with `@`, is read as time since the Unix
+/// epoch in [`ParseOptions::epoch_unit`] (seconds by default), or from
+/// another start with [`ParseOptions::epoch_reference`]. It may be
+/// negative and may carry a fraction, e.g. `-1.5`. RFC3339 timestamps such as `2025-09-22T21:00:00Z` or
+/// `2025-09-22T21:00:00+10:00` keep their offset; without one, as in
+/// `2025-09-22T21:00`, the ISO form is a wall-clock time like the others. The
+/// literal `now` is the current instant, to the second.
+///
+/// The layout the `date` command prints, as in `Mon Sep 22 11:00:00 UTC 2025`,
+/// is accepted too; like any zone word its `UTC` (or `AEST`, say) fixes the
+/// offset, so `date -u` output is read as UTC.
+///
+/// A military zone letter right after the time fixes the offset in whole
+/// hours, as in `21:00Z 22-09-2025` (UTC) or `21:00K` (+10:00). `A` to `I`
+/// are +1 to +9, `K`, `L` and `M` are +10 to +12, `N` to `Y` are -1 to -12
+/// and `Z` is +0. `J`, the observer's own time, is not accepted.
+///
+/// ISO 8601's basic format, `20250922T210000` or `20250922210000`, is a
+/// wall-clock time; with a trailing `Z`, as `--format ical` writes it, it is
+/// UTC. Fourteen bare digits are an epoch whenever they can be one, which
+/// only seconds since 1970 cannot.
+///
+/// Numeric dates are always day first unless they lead with a four-digit
+/// year, so `21:00 01-02-03` is 1 February 2003 (`dd-mm-yy`), never a
+/// year-first or month-first reading. The forms are tried in a fixed order,
+/// and the first that matches wins: RFC3339, the ISO form without an offset,
+/// `now`, epoch numbers, the basic format, inputs with a zone or offset word, a bare time, a
+/// relative day, ISO week dates, then time-first dates (ISO, then day first),
+/// date-first dates in the same order, the layout `date` prints, and finally
+/// a date on its own.
+///
+/// A seconds field of `60` is accepted as a leap second, using chrono's
+/// representation (second 59 with a nanosecond count of a billion or more),
+/// and survives conversion, so `23:59:60` prints back as `:60`. It is not
+/// checked against the leap-second table, and since Unix time has no leap
+/// seconds it has the same epoch as `:59`.
+pub fn parse(s: &str) -> Result<Input, ParseError> {
+    parse_traced(s, &ParseOptions::default()).map(|(input, _)| input)
+}
+
+/// Like [`parse`], but takes [`ParseOptions`] and also reports which format
+/// matched and whether a two-digit year was rewritten.
+pub fn parse_traced(s: &str, opts: &ParseOptions) -> Result<(Input, Trace), ParseError> {
+    if s.trim().is_empty() {
+        return Err(ParseError::Empty);
+    }
+    let s = &*tidy_whitespace(s);
+    let s = &*tidy_meridiem(s);
+    let s = &*tidy_noon(s);
+    let instant = |dt: DateTime<FixedOffset>, format| {
+        let trace = Trace { format, parsed: dt.naive_local(), year_rewrite: None, year_first: None };
+        (Input::Instant(dt), trace)
+    };
+    let naive = |ndt: NaiveDateTime, format| {
+        let trace = Trace { format, parsed: ndt, year_rewrite: None, year_first: None };
+        (Input::Naive(ndt), trace)
+    };
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(instant(dt, "rfc3339"));
+    }
+
+    let shape = Shape::of(s);
+
+    // RFC3339 without the offset is still a naive wall-clock time
+    const ISO_T: [Pattern; 2] = [pattern("%Y-%m-%dT%H:%M:%S%.f"), pattern("%Y-%m-%dT%H:%M")];
+    for fmt in shape.candidates(ISO_T) {
+        if let Ok(ndt) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Ok(naive(ndt, fmt));
+        }
+    }
+
+    if s.eq_ignore_ascii_case("now") {
+        // Whole seconds, like every other input form
+        return Ok(instant(Utc::now().trunc_subsecs(0).fixed_offset(), "now"));
+    }
+
+    let number = s.strip_prefix('@').unwrap_or(s);
+    if is_epoch(number) {
+        // Fourteen digits are only the basic format when they cannot be an epoch
+        return match parse_epoch(number, opts.epoch_unit, opts.epoch_reference) {
+            Some(utc) => Ok(instant(utc.fixed_offset(), "epoch")),
+            None => iso_basic(s).map(|(ndt, fmt)| naive(ndt, fmt)).ok_or_else(|| ParseError::EpochOutOfRange(s.to_string())),
+        };
+    }
+
+    // With the `Z` that --format ical writes it is UTC; without, a wall-clock time
+    if let Some((ndt, "%Y%m%dT%H%M%S")) = s.strip_suffix(['Z', 'z']).and_then(iso_basic) {
+        return Ok(instant(ndt.and_utc().fixed_offset(), "%Y%m%dT%H%M%SZ"));
+    }
+    if let Some((ndt, fmt)) = iso_basic(s) {
+        return Ok(naive(ndt, fmt));
+    }
+
+    // A zone named in the input pins the wall-clock time to that zone's offset
+    if let Some((abbr, rest)) = split_zone_word(s) {
+        let zone = match abbrev::zones_for(abbr) {
+            [zone] => *zone,
+            zones => {
+                let candidates = zones.iter().map(|z| z.name().to_string()).collect();
+                return Err(ParseError::AmbiguousZone { abbr: abbr.to_string(), candidates });
+            }
+        };
+        let (input, trace) = parse_traced(&rest, opts)?;
+        let Input::Naive(ndt) = input else { return Err(ParseError::UnrecognizedFormat) };
+        let offset = abbrev::offset_for(abbr, zone, ndt);
+        let dt = offset.from_local_datetime(&ndt).single().expect("fixed offsets are unambiguous");
+        return Ok((Input::Instant(dt), trace));
+    }
+
+    let military = || split_military_zone(s).map(|(offset, rest)| (Ok(offset), rest));
+    if let Some((offset, rest)) = split_offset_word(s).or_else(military) {
+        let (input, trace) = parse_traced(&rest, opts)?;
+        let Input::Naive(ndt) = input else { return Err(ParseError::UnrecognizedFormat) };
+        let dt = offset?.from_local_datetime(&ndt).single().expect("fixed offsets are unambiguous");
+        return Ok((Input::Instant(dt), trace));
+    }
+
+    if let Some((t, fmt)) = parse_time(s) {
+        return Ok(naive(NaiveDateTime::new(bare_time_date(opts)?, t), fmt));
+    }
+
+    if relative_day(s, opts).is_some() {
+        return Err(ParseError::MissingTime(s.to_string()));
+    }
+    if let Some((time, word)) = s.rsplit_once(' ')
+        && let Some(date) = relative_day(word, opts)
+    {
+        return match parse_time(time) {
+            Some((t, fmt)) => Ok(naive(NaiveDateTime::new(date, t), fmt)),
+            None => Err(impossible_time(time).unwrap_or_else(|| ParseError::UnrecognizedTime {
+                time: time.to_string(),
+                word: word.to_string(),
+            })),
+        };
+    }
+
+    if let Some((ndt, fmt)) = iso_week_datetime(s)? {
+        return Ok(naive(ndt, fmt));
+    }
+
+    // %Y takes any number of digits, so ISO order is only tried when the date
+    // leads with a four-digit year; otherwise `22-09-25` would become year 22
+    if leads_with_year(s.rsplit(' ').next()) {
+        for fmt in shape.candidates(ISO_TIME_FIRST) {
+            if let Ok(ndt) = NaiveDateTime::parse_from_str(s, fmt) {
+                return Ok(naive(ndt, fmt));
+            }
+        }
+    }
+
+    // %Y also takes two digits, so only the typed width says whether a year
+    // below 100 was abbreviated; `01-01-0025` means year 25
+    let short_year = s
+        .split(' ')
+        .find(|w| w.contains(['-', '/']))
+        .and_then(|date| date.rsplit(['-', '/']).next())
+        .filter(|y| y.len() <= 2);
+    let day_first = |ndt: NaiveDateTime, format| {
+        if let Some(year) = short_year.filter(|_| opts.strict_years) {
+            return Err(ParseError::TwoDigitYear(year.to_string()));
+        }
+        let short_year = short_year.is_some();
+        let fixed = if short_year { fix_two_digit_year(ndt, opts.year_pivot) } else { ndt };
+        let year_rewrite = (fixed != ndt).then(|| (ndt.year(), fixed.year()));
+        let year_first = year_first_reading(s, opts.year_pivot).filter(|&date| date != fixed.date());
+        Ok((Input::Naive(fixed), Trace { format, parsed: ndt, year_rewrite, year_first }))
+    };
+    // A leading four-digit field is a year, however a day-first form might
+    // read it, so such dates only ever went through the ISO forms above
+    let iso_date = s.split(' ').any(|w| leads_with_year(Some(w)));
+
+    for fmt in shape.candidates(FORMATS).filter(|_| !iso_date) {
+        if let Ok(ndt) = NaiveDateTime::parse_from_str(s, fmt) {
+            return day_first(ndt, fmt);
+        }
+    }
+
+    // The same dates written before the time, tried last so that nothing the
+    // time-first forms accept changes meaning
+    if leads_with_year(s.split(' ').next()) {
+        for fmt in shape.candidates(ISO_DATE_FIRST) {
+            if let Ok(ndt) = NaiveDateTime::parse_from_str(s, fmt) {
+                return Ok(naive(ndt, fmt));
+            }
+        }
+    }
+
+    for fmt in shape.candidates(DATE_FIRST).filter(|_| !iso_date) {
+        if let Ok(ndt) = NaiveDateTime::parse_from_str(s, fmt) {
+            return day_first(ndt, fmt);
+        }
+    }
+
+    // The layout `date` prints, as in `Mon Sep 22 11:00:00 UTC 2025`. The zone
+    // word went with the branch above, so only the wall clock is left here
+    const DATE_COMMAND: [Pattern; 1] = [pattern("%a %b %e %H:%M:%S %Y")];
+    for fmt in shape.candidates(DATE_COMMAND) {
+        if let Ok(ndt) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Ok(naive(ndt, fmt));
+        }
+    }
+
+    // A date on its own is its midnight
+    if let Some(date) = iso_week_date(s) {
+        return Ok(naive(date?.and_time(NaiveTime::MIN), "%G-W%V-%u"));
+    }
+    if leads_with_year(Some(s))
+        && let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+    {
+        return Ok(naive(date.and_time(NaiveTime::MIN), "%Y-%m-%d"));
+    }
+    const DATES: [Pattern; 2] = [pattern("%d-%m-%Y"), pattern("%d/%m/%Y")];
+    for fmt in shape.candidates(DATES).filter(|_| !iso_date) {
+        if let Ok(date) = NaiveDate::parse_from_str(s, fmt) {
+            return day_first(date.and_time(NaiveTime::MIN), fmt);
+        }
+    }
+
+    if let Some(msg) = impossible_time(s).or_else(|| impossible_day(s, opts.year_pivot)) {
+        return Err(msg);
+    }
+
+    Err(ParseError::UnrecognizedFormat)
+}
+
+/// Every reading of `s` worth offering, each with the strftime pattern it
+/// amounts to. The first is what [`parse_traced`] gives. A numeric date
+/// that does not lead with a four-digit year adds its month-first reading
+/// and, when all three fields have two digits, its year-first one, if those
+/// are real dates and differ from the readings before them. Empty when `s`
+/// does not parse at all.
+///
+/// ```
+/// use utc_time::{parse_all, ParseOptions};
+///
+/// let readings = parse_all("21:00 01-02-03", &ParseOptions::default());
+/// let labels: Vec<&str> = readings.iter().map(|(_, format)| format.as_str()).collect();
+/// assert_eq!(labels, ["%H:%M %d-%m-%Y", "%H:%M %m-%d-%Y", "%H:%M %Y-%m-%d"]);
+/// assert_eq!(readings[1].0.to_string(), "2003-01-02 21:00:00");
+/// assert_eq!(parse_all("21:00 22-09-2025", &ParseOptions::default()).len(), 1);
+/// ```
+pub fn parse_all(s: &str, opts: &ParseOptions) -> Vec<(NaiveDateTime, String)> {
+    let Ok((input, trace)) = parse_traced(s, opts) else { return Vec::new() };
+    let mut readings = vec![(input.naive(), trace.format.to_string())];
+    let Input::Naive(_) = input else { return readings };
+    let s = tidy_whitespace(s);
+    let words: Vec<&str> = s.split(' ').collect();
+    let Some(at) = words.iter().position(|w| numeric_date_fields(w).is_some()) else { return readings };
+    let (sep, [a, b, c]) = numeric_date_fields(words[at]).expect("found above");
+    if a.len() == 4 {
+        return readings;
+    }
+    // The day moves into the other field's place, so its specifier swaps too.
+    // Two-digit years are read with `%Y` and then moved by the pivot
+    let mut others = vec![(format!("{b}{sep}{a}{sep}{c}"), ["%m"; 2])];
+    if [a, b, c].iter().all(|f| f.len() == 2) {
+        others.push((format!("{c}{sep}{b}{sep}{a}"), ["%Y", "%y"]));
+    }
+    for (date, fields) in others {
+        let mut rewritten = words.clone();
+        rewritten[at] = &date;
+        let Ok((Input::Naive(ndt), trace)) = parse_traced(&rewritten.join(" "), opts) else { continue };
+        if readings.iter().all(|(seen, _)| *seen != ndt) {
+            let field = fields.into_iter().find(|f| trace.format.contains(f)).unwrap_or(fields[0]);
+            let label = trace.format.replace("%d", "\0").replace(field, "%d").replace('\0', field);
+            readings.push((ndt, label));
+        }
+    }
+    readings
+}
+
+// `01-02-03` or `1/2/2003`: three runs of digits split by one kind of separator
+fn numeric_date_fields(word: &str) -> Option<(char, [&str; 3])> {
+    let sep = word.chars().find(|c| matches!(c, '-' | '/'))?;
+    let fields: Vec<&str> = word.split(sep).collect();
+    let [a, b, c] = fields[..] else { return None };
+    let digits = |f: &str| !f.is_empty() && f.len() <= 4 && f.bytes().all(|b| b.is_ascii_digit());
+    ([a, b, c].into_iter().all(digits)).then_some((sep, [a, b, c]))
+}
+
+/// Why [`TimeConverter::to_target`] could not place a wall-clock time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertError {
+    /// The time falls in a gap skipped by a DST change.
+    NonExistent { zone: Tz, time: NaiveDateTime },
+    /// The time is repeated by a DST change and could be either instant.
+    Ambiguous { earliest: DateTime<Tz>, latest: DateTime<Tz> },
+    /// The instant would fall outside chrono's range of dates, which only
+    /// happens within a day of [`NaiveDateTime::MIN`] or [`NaiveDateTime::MAX`].
+    OutOfRange { zone: Tz, time: NaiveDateTime },
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::NonExistent { zone, time } => {
+                write!(f, "{time} does not exist in {zone} (skipped by a DST change)")
+            }
+            ConvertError::Ambiguous { earliest, latest } => write!(
+                f,
+                "{} is ambiguous in {} (repeated by a DST change): {} or {}",
+                earliest.naive_local(),
+                earliest.timezone(),
+                earliest.to_rfc3339(),
+                latest.to_rfc3339(),
+            ),
+            ConvertError::OutOfRange { zone, time } => write!(f, "{time} in {zone} is beyond the supported range of dates"),
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+/// The IANA zone for a city on its own, ignoring case, as in `Brisbane` or
+/// `new york` (spaces stand for the underscores). When several names share
+/// the city, as `America/Indianapolis` and `America/Indiana/Indianapolis` do,
+/// the shortest wins.
+///
+/// ```
+/// assert_eq!(utc_time::zone_for_city("london"), Some(chrono_tz::Tz::Europe__London));
+/// assert_eq!(utc_time::zone_for_city("Europe/London"), None);
+/// ```
+pub fn zone_for_city(name: &str) -> Option<Tz> {
+    let city = name.trim().replace(' ', "_");
+    chrono_tz::TZ_VARIANTS
+        .iter()
+        .filter(|tz| tz.name().contains('/') && tz.name().rsplit('/').next().is_some_and(|c| c.eq_ignore_ascii_case(&city)))
+        .min_by_key(|tz| tz.name().len())
+        .copied()
+}
+
+/// The IANA zone whose name is closest to `name`, ignoring case, for
+/// suggesting a fix to a mistyped zone. The city alone also counts, so
+/// `Brisbane` finds `Australia/Brisbane`. `None` when nothing is within a
+/// few edits, so unrelated words get no suggestion.
+///
+/// ```
+/// assert_eq!(utc_time::closest_zone("Australia/Sydney2"), Some(chrono_tz::Tz::Australia__Sydney));
+/// assert_eq!(utc_time::closest_zone("tomorrow"), None);
+/// ```
+pub fn closest_zone(name: &str) -> Option<Tz> {
+    let name = name.to_ascii_lowercase();
+    let limit = (name.chars().count() / 4).clamp(1, 3);
+    chrono_tz::TZ_VARIANTS
+        .iter()
+        .map(|tz| {
+            let full = tz.name().to_ascii_lowercase();
+            let city = full.rsplit('/').next().unwrap_or(&full);
+            let whole = edit_distance(&name, &full);
+            // Ties go to the whole name, so `UTC2` suggests `UTC` over `Etc/UTC`
+            ((whole.min(edit_distance(&name, city)), whole), *tz)
+        })
+        .filter(|&((distance, _), _)| distance <= limit)
+        .min_by_key(|&(key, _)| key)
+        .map(|(_, tz)| tz)
+}
+
+// Levenshtein distance, one row at a time
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Reads wall-clock times in one zone and expresses them in another.
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use chrono_tz::Tz;
+/// use utc_time::TimeConverter;
+///
+/// let converter = TimeConverter::new(Tz::Australia__Brisbane, Tz::UTC);
+/// let ndt = NaiveDate::from_ymd_opt(2025, 9, 22).unwrap().and_hms_opt(21, 0, 0).unwrap();
+/// assert_eq!(converter.to_target(ndt).unwrap().to_rfc3339(), "2025-09-22T11:00:00+00:00");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeConverter {
+    pub from: Tz,
+    pub to: Tz,
+}
+
+impl TimeConverter {
+    pub fn new(from: Tz, to: Tz) -> TimeConverter {
+        TimeConverter { from, to }
+    }
+
+    /// The instant `ndt` names on `from`'s wall clock, shown in `to`. Times
+    /// that a DST change skips or repeats in `from` are errors.
+    pub fn to_target(&self, ndt: NaiveDateTime) -> Result<DateTime<Tz>, ConvertError> {
+        let day = TimeDelta::days(1);
+        let near_edge = ndt.checked_sub_signed(day).and(ndt.checked_add_signed(day)).is_none();
+        match self.from.from_local_datetime(&ndt) {
+            LocalResult::Single(dt) => Ok(dt.with_timezone(&self.to)),
+            LocalResult::None if near_edge => Err(ConvertError::OutOfRange { zone: self.from, time: ndt }),
+            LocalResult::None => Err(ConvertError::NonExistent { zone: self.from, time: ndt }),
+            LocalResult::Ambiguous(earliest, latest) => Err(ConvertError::Ambiguous { earliest, latest }),
+        }
+    }
+
+    /// The current instant in `to`, to the second.
+    pub fn now(&self) -> DateTime<Tz> {
+        Utc::now().trunc_subsecs(0).with_timezone(&self.to)
+    }
+}
+
+/// Which way a conversion goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// Read the input as wall-clock time in the zone and print UTC.
+    ToUtc,
+    /// Read the input as UTC and print the zone's wall-clock time.
+    ToLocal,
+}
+
+/// Both sides of a converted time, as returned by [`convert`].
+///
+/// With the `serde` feature it is written as `utc`, `local` (RFC3339
+/// strings), `zone` (an IANA name, which `local` alone would lose) and
+/// `direction`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "ConversionRepr", try_from = "ConversionRepr")
+)]
+pub struct Conversion {
+    pub utc: DateTime<Utc>,
+    pub local: DateTime<Tz>,
+    pub direction: Direction,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ConversionRepr {
+    utc: DateTime<Utc>,
+    local: DateTime<FixedOffset>,
+    zone: Tz,
+    direction: Direction,
+}
+
+#[cfg(feature = "serde")]
+impl From<Conversion> for ConversionRepr {
+    fn from(c: Conversion) -> ConversionRepr {
+        let zone = c.local.timezone();
+        ConversionRepr { utc: c.utc, local: c.local.fixed_offset(), zone, direction: c.direction }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ConversionRepr> for Conversion {
+    type Error = String;
+
+    fn try_from(repr: ConversionRepr) -> Result<Conversion, String> {
+        let local = repr.local.with_timezone(&repr.zone);
+        if local.fixed_offset() != repr.local {
+            return Err(format!("{} is not a {} time", repr.local, repr.zone));
+        }
+        if local != repr.utc {
+            return Err(format!("utc {} and local {} are different instants", repr.utc, repr.local));
+        }
+        Ok(Conversion { utc: repr.utc, local, direction: repr.direction })
+    }
+}
+
+impl Conversion {
+    /// The same conversion `delta` later, or earlier if negative. The shift is
+    /// counted on the instant, so a DST change in between moves the local
+    /// side's offset rather than its length. `None` past chrono's range.
+    pub fn shifted(self, delta: TimeDelta) -> Option<Conversion> {
+        let utc = self.utc.checked_add_signed(delta)?;
+        Some(Conversion { utc, local: utc.with_timezone(&self.local.timezone()), ..self })
+    }
+}
+
+/// Converts `input` between UTC and `zone`. Wall-clock input is read in
+/// `zone` for [`Direction::ToUtc`] and as UTC for [`Direction::ToLocal`]; an
+/// [`Input::Instant`] already names its moment, so `direction` is only
+/// recorded.
+///
+/// ```
+/// use chrono_tz::Tz;
+/// use utc_time::{convert, parse, Direction};
+///
+/// let input = parse("21:00 22-09-2025").unwrap();
+/// let conversion = convert(input, Tz::Australia__Brisbane, Direction::ToUtc).unwrap();
+/// assert_eq!(conversion.utc.to_rfc3339(), "2025-09-22T11:00:00+00:00");
+/// assert_eq!(conversion.local.to_rfc3339(), "2025-09-22T21:00:00+10:00");
+/// ```
+pub fn convert(input: Input, zone: Tz, direction: Direction) -> Result<Conversion, ConvertError> {
+    let local = match input {
+        Input::Instant(dt) => dt.with_timezone(&zone),
+        Input::Naive(ndt) => {
+            let from = if direction == Direction::ToLocal { Tz::UTC } else { zone };
+            TimeConverter::new(from, zone).to_target(ndt)?
+        }
+    };
+    Ok(Conversion { utc: local.with_timezone(&Utc), local, direction })
+}
+
+/// The inputs in `reader`, one per line, each with its line number from 1.
+/// Lines are trimmed, which also drops the `\r` of a CRLF ending that
+/// [`BufRead::lines`] leaves behind when one is stray, and blank lines are
+/// skipped. Lines are read as the iterator is advanced, never all at once.
+pub fn input_lines<R: BufRead>(reader: R) -> impl Iterator<Item = (usize, io::Result<String>)> {
+    reader
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.map(|line| line.trim().to_string())))
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.is_empty()))
+}
+
+/// Why a line of [`parse_lines`] or [`convert_lines`] gave no result.
+#[derive(Debug)]
+pub enum LineError {
+    /// Reading failed; later lines may still follow.
+    Io(io::Error),
+    /// Line `line` is not a time [`parse_traced`] accepts with the options given.
+    Parse { line: usize, error: ParseError },
+    /// Line `line` parsed but could not be placed on the clock.
+    Convert { line: usize, error: ConvertError },
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineError::Io(e) => write!(f, "{e}"),
+            LineError::Parse { line, error } => write!(f, "line {line}: {error}"),
+            LineError::Convert { line, error } => write!(f, "line {line}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for LineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LineError::Io(e) => Some(e),
+            LineError::Parse { error, .. } => Some(error),
+            LineError::Convert { error, .. } => Some(error),
+        }
+    }
+}
+
+// The pattern the last line of a batch matched, tried first on the next.
+// Only the time-and-date grids are remembered, and a hit only counts when the
+// line is exactly how that pattern prints a year in 0..=9999: such text has no
+// zone, offset, `T` or spare whitespace, no two-digit year and no leading
+// four-digit field in the wrong place, so no branch `parse_traced` tries
+// earlier could have taken it, and the day-first year fixes are no-ops.
+// Anything else goes through the full probe, and what it matches is remembered
+#[derive(Default)]
+struct FormatCache {
+    last: Option<&'static str>,
+}
+
+impl FormatCache {
+    fn parse(&mut self, s: &str, opts: &ParseOptions) -> Result<(Input, Trace), ParseError> {
+        if let Some(fmt) = self.last
+            && let Ok(ndt) = NaiveDateTime::parse_from_str(s, fmt)
+            && (0..=9999).contains(&ndt.year())
+            && ndt.format(fmt).to_string() == s
+        {
+            return Ok((Input::Naive(ndt), Trace { format: fmt, parsed: ndt, year_rewrite: None, year_first: None }));
+        }
+        let result = parse_traced(s, opts);
+        if let Ok((_, trace)) = &result {
+            let grids = ISO_TIME_FIRST.iter().chain(&FORMATS).chain(&ISO_DATE_FIRST).chain(&DATE_FIRST);
+            self.last = grids.map(|p| p.fmt).find(|&fmt| fmt == trace.format);
+        }
+        result
+    }
+}
+
+/// One of [`parse_lines`], with the text it was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedLine {
+    /// The line number, from 1.
+    pub line: usize,
+    /// The line as read, trimmed.
+    pub text: String,
+    pub input: Input,
+    pub trace: Trace,
+}
+
+/// Parses each of [`input_lines`] lazily, as [`parse_traced`] would with
+/// `opts`. The pattern the last line matched is tried first, so input in
+/// one layout skips most of the probe; the results are the same either way.
+///
+/// ```
+/// use utc_time::{parse_lines, LineError, ParseOptions};
+///
+/// let opts = ParseOptions { year_pivot: 20, ..ParseOptions::default() };
+/// let lines: Vec<_> = parse_lines("21:00 22-09-25\nnot a time\n".as_bytes(), &opts).collect();
+/// let first = lines[0].as_ref().unwrap();
+/// assert_eq!((first.line, first.text.as_str()), (1, "21:00 22-09-25"));
+/// assert_eq!(first.input.naive().to_string(), "1925-09-22 21:00:00");
+/// assert!(matches!(lines[1], Err(LineError::Parse { line: 2, .. })));
+/// ```
+pub fn parse_lines<R: BufRead>(reader: R, opts: &ParseOptions) -> impl Iterator<Item = Result<ParsedLine, LineError>> {
+    let (opts, mut cache) = (*opts, FormatCache::default());
+    input_lines(reader).map(move |(line, text)| {
+        let text = text.map_err(LineError::Io)?;
+        let (input, trace) = cache.parse(&text, &opts).map_err(|error| LineError::Parse { line, error })?;
+        Ok(ParsedLine { line, text, input, trace })
+    })
+}
+
+/// Converts each of [`parse_lines`] lazily, so memory stays flat however
+/// long `reader` is. Wall-clock lines are read on `converter.from`'s clock
+/// and instants stand as they are; `local` is the result in `converter.to`,
+/// and `direction` is [`Direction::ToLocal`] when `from` is UTC and
+/// [`Direction::ToUtc`] otherwise.
+///
+/// ```
+/// use chrono_tz::Tz;
+/// use utc_time::{convert_lines, LineError, ParseOptions, TimeConverter};
+///
+/// let converter = TimeConverter::new(Tz::Australia__Brisbane, Tz::UTC);
+/// let lines = "21:00 22-09-2025\n\nnot a time\n@0\n".as_bytes();
+/// let results: Vec<_> = convert_lines(lines, &converter, &ParseOptions::default()).collect();
+/// assert_eq!(results[0].as_ref().unwrap().utc.to_rfc3339(), "2025-09-22T11:00:00+00:00");
+/// assert!(matches!(results[1], Err(LineError::Parse { line: 3, .. })));
+/// assert_eq!(results[2].as_ref().unwrap().utc.timestamp(), 0);
+/// ```
+pub fn convert_lines<R: BufRead>(
+    reader: R,
+    converter: &TimeConverter,
+    opts: &ParseOptions,
+) -> impl Iterator<Item = Result<Conversion, LineError>> {
+    let converter = *converter;
+    let direction = if converter.from == Tz::UTC { Direction::ToLocal } else { Direction::ToUtc };
+    parse_lines(reader, opts).map(move |parsed| {
+        let ParsedLine { line, input, .. } = parsed?;
+        let local = match input {
+            Input::Instant(dt) => dt.with_timezone(&converter.to),
+            Input::Naive(ndt) => converter.to_target(ndt).map_err(|error| LineError::Convert { line, error })?,
+        };
+        Ok(Conversion { utc: local.with_timezone(&Utc), local, direction })
+    })
+}
+
+/// The offset changes `tz` made during `year` on its own calendar, in order,
+/// each as the UTC instant it took effect and the offset from then on.
+/// Changes that keep the total offset, such as a renamed abbreviation, are
+/// not listed. Empty for zones without changes that year and for years
+/// chrono cannot represent.
+///
+/// The zone database is sampled hourly and each change narrowed to the
+/// second, so two changes less than an hour apart would be missed.
+///
+/// ```
+/// use chrono_tz::Tz;
+/// use utc_time::transitions_in_year;
+///
+/// let changes = transitions_in_year(Tz::Australia__Sydney, 2025);
+/// assert_eq!(changes[0].0.to_string(), "2025-04-05 16:00:00");
+/// assert_eq!(changes[0].1.to_string(), "+10:00");
+/// assert!(transitions_in_year(Tz::Australia__Brisbane, 2025).is_empty());
+/// ```
+pub fn transitions_in_year(tz: Tz, year: i32) -> Vec<(NaiveDateTime, FixedOffset)> {
+    let jan1 = |year| NaiveDate::from_ymd_opt(year, 1, 1).map(|d| d.and_time(NaiveTime::MIN));
+    // A day either side covers every offset's idea of New Year
+    let start = jan1(year).and_then(|d| d.checked_sub_days(Days::new(1)));
+    let end = year.checked_add(1).and_then(jan1).and_then(|d| d.checked_add_days(Days::new(1)));
+    let (Some(start), Some(end)) = (start, end) else {
+        return Vec::new();
+    };
+    let offset = |utc: NaiveDateTime| tz.offset_from_utc_datetime(&utc).fix();
+    let hour = TimeDelta::hours(1);
+    let mut changes = Vec::new();
+    let mut at = start;
+    while at < end {
+        let next = at + hour;
+        if offset(at) != offset(next) {
+            // The first second with the new offset
+            let (mut before, mut after) = (at, next);
+            while after - before > TimeDelta::seconds(1) {
+                let mid = before + (after - before) / 2;
+                if offset(mid) == offset(at) { before = mid } else { after = mid }
+            }
+            let new = offset(after);
+            if (after + new).year() == year {
+                changes.push((after, new));
+            }
+        }
+        at = next;
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    fn message(s: &str) -> String {
+        parse_input(s).unwrap_err().to_string()
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, s).unwrap()
+    }
+
+    #[test]
+    fn two_digit_years_split_at_the_pivot() {
+        assert_eq!(map_two_digit_year(0, DEFAULT_YEAR_PIVOT), 2000);
+        assert_eq!(map_two_digit_year(68, DEFAULT_YEAR_PIVOT), 2068);
+        assert_eq!(map_two_digit_year(69, DEFAULT_YEAR_PIVOT), 1969);
+        assert_eq!(map_two_digit_year(99, DEFAULT_YEAR_PIVOT), 1999);
+        assert_eq!(map_two_digit_year(30, 29), 1930);
+    }
+
+    #[test]
+    fn the_pivot_holds_through_parsing() {
+        let opts = ParseOptions::default();
+        for (s, year) in [("23:59 31-12-68", 2068), ("00:00 01-01-69", 1969), ("31/12/68 23:59", 2068), ("01-01-69", 1969)] {
+            let (input, trace) = parse_traced(s, &opts).unwrap();
+            assert_eq!(input.naive().year(), year, "{s}");
+            assert_eq!(trace.year_rewrite.map(|(_, to)| to), Some(year), "{s}");
+        }
+    }
+
+    #[test]
+    fn full_years_are_left_alone() {
+        assert_eq!(map_two_digit_year(100, DEFAULT_YEAR_PIVOT), 100);
+        assert_eq!(map_two_digit_year(2025, DEFAULT_YEAR_PIVOT), 2025);
+        assert_eq!(map_two_digit_year(-1, DEFAULT_YEAR_PIVOT), -1);
+    }
+
+    #[test]
+    fn leap_seconds_are_kept() {
+        let ndt = parse_input("23:59:60 31-12-2016").unwrap();
+        assert_eq!((ndt.second(), ndt.nanosecond()), (59, 1_000_000_000));
+        let utc = TimeConverter::new(Tz::UTC, Tz::UTC).to_target(ndt).unwrap();
+        assert_eq!(utc.to_rfc3339(), "2016-12-31T23:59:60+00:00");
+        assert_eq!(utc.timestamp(), at(2016, 12, 31, 23, 59, 59).and_utc().timestamp());
+        assert_eq!(message("23:59:61 31-12-2016"), "second must be 0–59, or 60 for a leap second, got 61");
+    }
+
+    #[test]
+    fn twelve_hour_seconds_take_a_date() {
+        let expected = at(2025, 9, 22, 21, 0, 30);
+        assert_eq!(parse_input("09:00:30 PM 22-09-2025"), Ok(expected));
+        assert_eq!(parse_input("09:00:30 PM 22/09/2025"), Ok(expected));
+        assert_eq!(parse_input("22-09-2025 9:00:30 PM"), Ok(expected));
+        let (input, trace) = parse_traced("09:00:30 PM 22/09/25", &ParseOptions::default()).unwrap();
+        assert_eq!(input, Input::Naive(expected));
+        assert_eq!(trace.year_rewrite, Some((25, 2025)));
+    }
+
+    #[test]
+    fn bare_dates_mean_midnight() {
+        let midnight = at(2025, 9, 22, 0, 0, 0);
+        for input in ["22-09-2025", "22/09/2025", "22-09-25", "22/09/25", "2025-09-22", "2025-W39-1"] {
+            assert_eq!(parse_input(input), Ok(midnight), "{input}");
+        }
+        assert_eq!(message("31-02-2025"), "February has no day 31");
+    }
+
+    #[test]
+    fn ambiguous_numeric_dates_read_day_first() {
+        let winner = |s| parse_traced(s, &ParseOptions::default()).map(|(input, trace)| (input.naive(), trace.format));
+        assert_eq!(winner("21:00 01-02-03"), Ok((at(2003, 2, 1, 21, 0, 0), "%H:%M %d-%m-%Y")));
+        assert_eq!(winner("21:00 01/02/03"), Ok((at(2003, 2, 1, 21, 0, 0), "%H:%M %d/%m/%Y")));
+        assert_eq!(winner("01-02-03 21:00"), Ok((at(2003, 2, 1, 21, 0, 0), "%d-%m-%Y %H:%M")));
+        assert_eq!(winner("01-02-03"), Ok((at(2003, 2, 1, 0, 0, 0), "%d-%m-%Y")));
+        assert_eq!(winner("21:00 01-02-2003"), Ok((at(2003, 2, 1, 21, 0, 0), "%H:%M %d-%m-%Y")));
+        // Only a four-digit lead makes it year first
+        assert_eq!(winner("21:00 2001-02-03"), Ok((at(2001, 2, 3, 21, 0, 0), "%H:%M %Y-%m-%d")));
+        assert_eq!(winner("21:00:00 01-02-03"), Ok((at(2003, 2, 1, 21, 0, 0), "%H:%M:%S%.f %d-%m-%Y")));
+        assert_eq!(winner("9:00 PM 01-02-03"), Ok((at(2003, 2, 1, 21, 0, 0), "%I:%M %p %d-%m-%Y")));
+    }
+
+    #[test]
+    fn skipped_patterns_could_never_match() {
+        let patterns = TIME_FORMATS.into_iter().chain([
+            pattern("%Y-%m-%dT%H:%M:%S%.f"),
+            pattern("%H:%M %d-%m-%Y"),
+            pattern("%I:%M:%S%.f %p %d/%m/%Y"),
+            pattern("%d/%m/%Y %I:%M %p"),
+            pattern("%d-%m-%Y"),
+        ]);
+        let inputs = [
+            "21:00", "21: 00", "21:00:30.5", "9:00 pm", "9:00:30 PM 22/09/25", "-2025-09-22T21:00:00",
+            "2025-09-22t21:00:00", "21:00 22-09-2025", "21:0022-09-2025", "22/09/2025 9:00 AM", "22-09-25",
+        ];
+        for p in patterns {
+            for input in inputs {
+                let parsed = NaiveDateTime::parse_from_str(input, p.fmt).is_ok()
+                    || NaiveTime::parse_from_str(input, p.fmt).is_ok()
+                    || NaiveDate::parse_from_str(input, p.fmt).is_ok();
+                assert!(!parsed || Shape::of(input).fits(p.shape), "{input} with {}", p.fmt);
+            }
+        }
+    }
+
+    #[test]
+    fn whitespace_runs_collapse() {
+        let expected = at(2025, 9, 22, 21, 0, 0);
+        for input in ["21:00   22-09-2025", "  21:00 22-09-2025  ", "21:00\t22-09-2025", "9:00 \t PM 22-09-2025\n"] {
+            assert_eq!(parse_input(input), Ok(expected), "{input:?}");
+        }
+        let (start, end) = split_range("21:00-23:00\t 22-09-2025").unwrap();
+        assert_eq!((start.as_str(), end.as_str()), ("21:00 22-09-2025", "23:00 22-09-2025"));
+    }
+
+    #[test]
+    fn meridiem_spellings_agree() {
+        let expected = at(2025, 9, 22, 21, 0, 0);
+        for input in [
+            "9:00 PM 22-09-2025",
+            "9:00PM 22-09-2025",
+            "9:00pm 22-09-2025",
+            "9:00 p.m. 22-09-2025",
+            "9:00p.m. 22-09-2025",
+            "9:00 P.M. 22-09-2025",
+            "22-09-2025 9:00PM",
+            "9:00:00pm 22/09/2025",
+        ] {
+            assert_eq!(parse_input(input), Ok(expected), "{input}");
+        }
+        assert_eq!(parse_input("12:30a.m. 22-09-2025"), Ok(at(2025, 9, 22, 0, 30, 0)));
+        assert_eq!(tidy_meridiem("21:00 22-09-2025"), "21:00 22-09-2025");
+        assert!(matches!(tidy_meridiem("9:00 pm"), Cow::Borrowed(_)));
+        assert_eq!(message("13:00PM 22-09-2025"), "hour must be 1–12 with AM/PM, got 13");
+    }
+
+    #[test]
+    fn shifts_count_real_time_across_dst() {
+        let ny = Tz::America__New_York;
+        let input = parse("23:00 08-03-2025").unwrap();
+        let conversion = convert(input, ny, Direction::ToUtc).unwrap();
+        // Crossing midnight and the 02:00 spring-forward: 4h later reads 04:00
+        let later = conversion.shifted(TimeDelta::hours(4)).unwrap();
+        assert_eq!(later.local.to_rfc3339(), "2025-03-09T04:00:00-04:00");
+        assert_eq!(later.utc - conversion.utc, TimeDelta::hours(4));
+        // A day is 24 hours, so across the change the wall clock moves by 25
+        let day = conversion.shifted(TimeDelta::days(1)).unwrap();
+        assert_eq!(day.local.to_rfc3339(), "2025-03-10T00:00:00-04:00");
+        let earlier = conversion.shifted(TimeDelta::minutes(-90)).unwrap();
+        assert_eq!(earlier.local.to_rfc3339(), "2025-03-08T21:30:00-05:00");
+    }
+
+    #[test]
+    fn boundary_years_fail_cleanly() {
+        assert_eq!(parse_input("00:00 01-01-0000"), Ok(at(0, 1, 1, 0, 0, 0)));
+        assert_eq!(parse_input("21:00 01-01-0025"), Ok(at(25, 1, 1, 21, 0, 0)));
+        assert_eq!(parse_input("23:59:59 31-12-9999"), Ok(at(9999, 12, 31, 23, 59, 59)));
+        let utc = convert(parse("23:59:59 31-12-9999").unwrap(), Tz::Australia__Brisbane, Direction::ToLocal).unwrap();
+        assert_eq!(utc.local.to_rfc3339(), "+10000-01-01T09:59:59+10:00");
+        // Year 262143 is past chrono's range; it is not a missing day
+        assert_eq!(parse_input("21:00 31-12-262143"), Err(ParseError::UnrecognizedFormat));
+        let first = NaiveDateTime::MIN;
+        let converted = TimeConverter::new(Tz::Australia__Brisbane, Tz::UTC).to_target(first);
+        assert_eq!(converted, Err(ConvertError::OutOfRange { zone: Tz::Australia__Brisbane, time: first }));
+        let last = parse("@8210266876799").unwrap();
+        assert!(convert(last, Tz::Pacific__Kiritimati, Direction::ToUtc).is_ok());
+    }
+
+    #[test]
+    fn close_zone_names_get_a_suggestion() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(closest_zone("Europe/Berln"), Some(Tz::Europe__Berlin));
+        assert_eq!(closest_zone("america/new york"), Some(Tz::America__New_York));
+        assert_eq!(closest_zone("Brisbane"), Some(Tz::Australia__Brisbane));
+        assert_eq!(closest_zone("Mars/Olympus_Mons"), None);
+    }
+
+    #[test]
+    fn transitions_follow_the_zone_database() {
+        let offset = |secs| FixedOffset::east_opt(secs).unwrap();
+        assert_eq!(
+            transitions_in_year(Tz::Australia__Sydney, 2025),
+            [(at(2025, 4, 5, 16, 0, 0), offset(10 * 3600)), (at(2025, 10, 4, 16, 0, 0), offset(11 * 3600))]
+        );
+        // Lord Howe moves by half an hour
+        assert_eq!(
+            transitions_in_year(Tz::Australia__Lord_Howe, 2025),
+            [(at(2025, 4, 5, 15, 0, 0), offset(37_800)), (at(2025, 10, 4, 15, 30, 0), offset(39_600))]
+        );
+        assert_eq!(
+            transitions_in_year(Tz::Europe__London, 2025),
+            [(at(2025, 3, 30, 1, 0, 0), offset(3600)), (at(2025, 10, 26, 1, 0, 0), offset(0))]
+        );
+        assert!(transitions_in_year(Tz::Australia__Brisbane, 2025).is_empty());
+        assert!(transitions_in_year(Tz::UTC, 262_143).is_empty());
+    }
+
+    #[test]
+    fn century_candidates_skip_missing_days() {
+        let ndt = at(2025, 9, 22, 21, 0, 0);
+        assert_eq!(century_candidates(ndt), [at(1925, 9, 22, 21, 0, 0), ndt]);
+        let leap = at(2000, 2, 29, 12, 0, 0);
+        assert_eq!(century_candidates(leap), [leap]);
+    }
+
+    #[test]
+    fn fix_two_digit_year_keeps_date_and_time() {
+        let fixed = fix_two_digit_year(at(25, 9, 22, 21, 0, 30), DEFAULT_YEAR_PIVOT);
+        assert_eq!(fixed, at(2025, 9, 22, 21, 0, 30));
+        assert_eq!(fix_two_digit_year(fixed, DEFAULT_YEAR_PIVOT), fixed);
+        assert_eq!(fix_two_digit_year(at(24, 2, 29, 0, 0, 0), DEFAULT_YEAR_PIVOT), at(2024, 2, 29, 0, 0, 0));
+        assert_eq!(fix_two_digit_year(at(72, 2, 29, 0, 0, 0), DEFAULT_YEAR_PIVOT), at(1972, 2, 29, 0, 0, 0));
+    }
+
+    #[test]
+    fn parses_every_dated_format() {
+        let cases = [
+            ("21:00:30 2025-09-22", at(2025, 9, 22, 21, 0, 30)),
+            ("21:00 2025-09-22", at(2025, 9, 22, 21, 0, 0)),
+            ("21:00:30 22-09-2025", at(2025, 9, 22, 21, 0, 30)),
+            ("21:00:30 22-09-25", at(2025, 9, 22, 21, 0, 30)),
+            ("21:00:30 22/09/2025", at(2025, 9, 22, 21, 0, 30)),
+            ("21:00:30 22/09/25", at(2025, 9, 22, 21, 0, 30)),
+            ("21:00 22-09-2025", at(2025, 9, 22, 21, 0, 0)),
+            ("21:00 22-09-25", at(2025, 9, 22, 21, 0, 0)),
+            ("21:00 22/09/2025", at(2025, 9, 22, 21, 0, 0)),
+            ("21:00 22/09/70", at(1970, 9, 22, 21, 0, 0)),
+            ("9:00 PM 22-09-2025", at(2025, 9, 22, 21, 0, 0)),
+            ("9:00 pm 22-09-25", at(2025, 9, 22, 21, 0, 0)),
+            ("12:00 AM 22/09/2025", at(2025, 9, 22, 0, 0, 0)),
+            ("12:00 PM 22/09/25", at(2025, 9, 22, 12, 0, 0)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_input(input), Ok(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn bare_times_land_on_today() {
+        let today = today_in(Brisbane);
+        assert_eq!(parse_input("21:00:30"), Ok(today.and_hms_opt(21, 0, 30).unwrap()));
+        assert_eq!(parse_input("21:00"), Ok(today.and_hms_opt(21, 0, 0).unwrap()));
+        assert_eq!(parse_input("9:00 PM"), Ok(today.and_hms_opt(21, 0, 0).unwrap()));
+        let opts = ParseOptions { today_zone: Tz::Pacific__Kiritimati, ..ParseOptions::default() };
+        let (input, _) = parse_traced("21:00", &opts).unwrap();
+        assert_eq!(input.naive().date(), today_in(Tz::Pacific__Kiritimati));
+    }
+
+    #[test]
+    fn base_date_replaces_today() {
+        let base = NaiveDate::from_ymd_opt(2025, 9, 22).unwrap();
+        let opts = ParseOptions { base_date: Some(base), ..ParseOptions::default() };
+        let read = |s| parse_traced(s, &opts).map(|(input, _)| input.naive());
+        assert_eq!(read("21:00"), Ok(at(2025, 9, 22, 21, 0, 0)));
+        assert_eq!(read("9:00 PM tomorrow"), Ok(at(2025, 9, 23, 21, 0, 0)));
+        // Monday the 22nd, so the next Monday is a week on
+        assert_eq!(read("09:00 monday"), Ok(at(2025, 9, 29, 9, 0, 0)));
+        assert_eq!(read("21:00 01-01-2020"), Ok(at(2020, 1, 1, 21, 0, 0)));
+    }
+
+    #[test]
+    fn instants_flatten_to_utc() {
+        assert_eq!(parse_input("@0"), Ok(at(1970, 1, 1, 0, 0, 0)));
+        assert_eq!(parse_input("-1.5"), Ok(at(1969, 12, 31, 23, 59, 58) + chrono::TimeDelta::milliseconds(500)));
+        assert_eq!(parse_input("2025-09-22T21:00:00+10:00"), Ok(at(2025, 9, 22, 11, 0, 0)));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for input in [
+            "",
+            "tomorrow",
+            "teatime today",
+            "25:00",
+            "21:60 22-09-2025",
+            "21:00 22-13-2025",
+            "21:00 22.09.2025",
+            "13:00 PM 22-09-2025",
+            "21:00 2025/09/22",
+            "@1.2.3",
+        ] {
+            assert!(parse_input(input).is_err(), "{input:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn names_the_missing_day() {
+        assert_eq!(message("21:00 31-02-2025"), "February has no day 31");
+        assert_eq!(message("21:00 29/02/25"), "February 2025 has no day 29 (2025 is not a leap year)");
+        assert_eq!(message("21:00 2025-04-31"), "April has no day 31");
+    }
+
+    #[test]
+    fn abbreviations_ignore_case() {
+        assert_eq!(abbrev::zones_for("pst"), [chrono_tz::Tz::America__Los_Angeles]);
+        assert_eq!(abbrev::zones_for("CST").len(), 3);
+        assert!(abbrev::zones_for("XYZ").is_empty());
+    }
+
+    #[test]
+    fn splits_ranges_only_between_two_times() {
+        assert_eq!(split_range("21:00-23:30"), Some(("21:00".into(), "23:30".into())));
+        assert_eq!(
+            split_range("21:00:15-01:00 22/09/25"),
+            Some(("21:00:15 22/09/25".into(), "01:00 22/09/25".into()))
+        );
+        assert_eq!(split_range("21:00 22-09-2025"), None);
+        assert_eq!(split_range("-1.5"), None);
+        assert_eq!(split_range("2025-09-22T21:00:00-05:00"), None);
+        assert_eq!(split_range("21:00-"), None);
+    }
+
+    #[test]
+    fn names_the_out_of_range_field() {
+        assert_eq!(message("25:00"), "hour must be 0–23, got 25");
+        assert_eq!(message("21:60 22-09-2025"), "minute must be 0–59, got 60");
+        assert_eq!(message("21:00:75"), "second must be 0–59, or 60 for a leap second, got 75");
+        assert_eq!(message("13:00 PM 22-09-2025"), "hour must be 1–12 with AM/PM, got 13");
+        assert_eq!(message("24:00 tomorrow"), "hour must be 0–23, got 24");
+    }
+
+    #[test]
+    fn parses_date_first_order() {
+        let cases = [
+            ("2025-09-22 21:00:30", at(2025, 9, 22, 21, 0, 30)),
+            ("2025-09-22 21:00", at(2025, 9, 22, 21, 0, 0)),
+            ("22-09-2025 21:00:30", at(2025, 9, 22, 21, 0, 30)),
+            ("22/09/2025 21:00", at(2025, 9, 22, 21, 0, 0)),
+            ("22-09-25 21:00", at(2025, 9, 22, 21, 0, 0)),
+            ("22/09/2025 9:00 PM", at(2025, 9, 22, 21, 0, 0)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_input(input), Ok(expected), "{input}");
+        }
+        assert_eq!(message("31-02-2025 21:00"), "February has no day 31");
+        assert_eq!(message("22-09-2025 25:00"), "hour must be 0–23, got 25");
+    }
+
+    #[test]
+    fn converter_reports_dst_gaps_and_repeats() {
+        let converter = TimeConverter::new(Tz::America__New_York, Tz::UTC);
+        assert_eq!(converter.to_target(at(2025, 7, 1, 12, 0, 0)).unwrap().to_rfc3339(), "2025-07-01T16:00:00+00:00");
+        let gap = at(2025, 3, 9, 2, 30, 0);
+        assert_eq!(
+            converter.to_target(gap),
+            Err(ConvertError::NonExistent { zone: Tz::America__New_York, time: gap })
+        );
+        assert!(matches!(converter.to_target(at(2025, 11, 2, 1, 30, 0)), Err(ConvertError::Ambiguous { .. })));
+        assert_eq!(converter.now().timezone(), Tz::UTC);
+    }
+
+    #[test]
+    fn errors_can_be_matched() {
+        assert_eq!(parse_input("25:00"), Err(ParseError::InvalidHour(25)));
+        assert_eq!(parse_input("21:00 31-04-25"), Err(ParseError::InvalidDate { day: 31, month: 4, year: 2025 }));
+        assert_eq!(parse_input("tomorrow"), Err(ParseError::MissingTime("tomorrow".into())));
+        assert_eq!(parse_input("whenever"), Err(ParseError::UnrecognizedFormat));
+        assert_eq!(parse_input(""), Err(ParseError::Empty));
+        assert_eq!(parse_input("   "), Err(ParseError::Empty));
+    }
+
+    #[test]
+    fn iso_without_offset_is_naive() {
+        assert_eq!(parse("2025-09-22T21:00"), Ok(Input::Naive(at(2025, 9, 22, 21, 0, 0))));
+        assert_eq!(parse("2025-09-22T21:00:30"), Ok(Input::Naive(at(2025, 9, 22, 21, 0, 30))));
+        assert!(parse("2025-09-22T25:00").is_err());
+    }
+
+    #[test]
+    fn fractional_seconds_round_trip() {
+        let ndt = at(2025, 9, 22, 21, 0, 30) + chrono::TimeDelta::nanoseconds(123_456_789);
+        assert_eq!(parse_input("21:00:30.123456789 22-09-2025"), Ok(ndt));
+        assert_eq!(parse_input("2025-09-22T21:00:30.123456789"), Ok(ndt));
+        assert_eq!(parse_input("21:00:30.5 2025-09-22"), Ok(at(2025, 9, 22, 21, 0, 30) + chrono::TimeDelta::milliseconds(500)));
+        assert_eq!(message("21:00:61.5"), "second must be 0–59, or 60 for a leap second, got 61");
+
+        // parse -> convert -> format -> parse lands on the same instant
+        let converter = TimeConverter::new(Tz::Australia__Brisbane, Tz::UTC);
+        let utc = converter.to_target(parse_input("21:00:30.123456789 22-09-2025").unwrap()).unwrap();
+        let printed = utc.to_rfc3339();
+        assert_eq!(printed, "2025-09-22T11:00:30.123456789+00:00");
+        assert_eq!(parse(&printed), Ok(Input::Instant(utc.fixed_offset())));
+        let local = converter.to_target(ndt).unwrap().with_timezone(&Tz::Australia__Brisbane);
+        assert_eq!(parse(&local.naive_local().format("%Y-%m-%dT%H:%M:%S%.f").to_string()), Ok(Input::Naive(ndt)));
+    }
+
+    #[test]
+    fn parses_iso_week_dates() {
+        assert_eq!(parse_input("2025-W39-1 21:00"), Ok(at(2025, 9, 22, 21, 0, 0)));
+        assert_eq!(parse_input("21:00:30 2025-w39-7"), Ok(at(2025, 9, 28, 21, 0, 30)));
+        // ISO 2026-W01 starts in the previous calendar year
+        assert_eq!(parse_input("2026-W01-1 09:00"), Ok(at(2025, 12, 29, 9, 0, 0)));
+        assert_eq!(message("2025-W53-1 21:00"), "2025 has no ISO week 53");
+        assert_eq!(message("2025-W39-8 21:00"), "ISO weekday must be 1–7 (Monday–Sunday), got 8 in 2025-W39");
+        assert_eq!(parse_input("2025-W39-1 25:00"), Err(ParseError::InvalidHour(25)));
+        assert!(parse_input("2025-W3-1 21:00").is_err());
+    }
+
+    #[test]
+    fn zone_abbreviations_in_input_fix_the_offset() {
+        let instant = |s| match parse(s) {
+            Ok(Input::Instant(dt)) => dt.to_rfc3339(),
+            other => panic!("{s}: {other:?}"),
+        };
+        assert_eq!(instant("21:00 AEST 22-09-2025"), "2025-09-22T21:00:00+10:00");
+        assert_eq!(instant("21:00 22-09-2025 AEDT"), "2025-09-22T21:00:00+11:00");
+        assert_eq!(instant("9:00 PM PST 01-07-2025"), "2025-07-01T21:00:00-08:00");
+        assert_eq!(instant("09:00 PDT 01-07-2025"), "2025-07-01T09:00:00-07:00");
+        assert_eq!(instant("12:00 UTC 01-01-2025"), "2025-01-01T12:00:00+00:00");
+        assert!(matches!(parse("21:00 CST 22-09-2025"), Err(ParseError::AmbiguousZone { .. })));
+        assert!(parse("21:00 aest 22-09-2025").is_err());
+    }
+
+    #[test]
+    fn numeric_offsets_in_input_fix_the_offset() {
+        let instant = |s| match parse(s) {
+            Ok(Input::Instant(dt)) => dt.to_rfc3339(),
+            other => panic!("{s}: {other:?}"),
+        };
+        assert_eq!(instant("21:00 +10:00 22-09-2025"), "2025-09-22T21:00:00+10:00");
+        assert_eq!(instant("22-09-2025 21:00 -0530"), "2025-09-22T21:00:00-05:30");
+        assert_eq!(instant("9:00 PM +09 01-07-2025"), "2025-07-01T21:00:00+09:00");
+        assert_eq!(message("21:00 +24:00 22-09-2025"), "offset must be within ±23:59, got +24:00");
+        assert!(parse("21:00 +10:00 AEST 22-09-2025").is_err());
+        assert!(parse("21:00 +1:00 22-09-2025").is_err());
+    }
+
+    // Expected UTC instants around the 2025 transitions; `None` marks a
+    // skipped wall-clock time and two instants a repeated one
+    const DST_GOLDEN: &[(Tz, &str, &[&str])] = &[
+        (Tz::America__New_York, "2025-03-09T01:59:59", &["2025-03-09T06:59:59+00:00"]),
+        (Tz::America__New_York, "2025-03-09T02:00:00", &[]),
+        (Tz::America__New_York, "2025-03-09T02:30:00", &[]),
+        (Tz::America__New_York, "2025-03-09T02:59:59", &[]),
+        (Tz::America__New_York, "2025-03-09T03:00:00", &["2025-03-09T07:00:00+00:00"]),
+        (Tz::America__New_York, "2025-11-02T00:59:59", &["2025-11-02T04:59:59+00:00"]),
+        (Tz::America__New_York, "2025-11-02T01:00:00", &["2025-11-02T05:00:00+00:00", "2025-11-02T06:00:00+00:00"]),
+        (Tz::America__New_York, "2025-11-02T01:30:00", &["2025-11-02T05:30:00+00:00", "2025-11-02T06:30:00+00:00"]),
+        (Tz::America__New_York, "2025-11-02T02:00:00", &["2025-11-02T07:00:00+00:00"]),
+        (Tz::Europe__London, "2025-03-30T00:59:59", &["2025-03-30T00:59:59+00:00"]),
+        (Tz::Europe__London, "2025-03-30T01:00:00", &[]),
+        (Tz::Europe__London, "2025-03-30T01:30:00", &[]),
+        (Tz::Europe__London, "2025-03-30T02:00:00", &["2025-03-30T01:00:00+00:00"]),
+        (Tz::Europe__London, "2025-10-26T00:59:59", &["2025-10-25T23:59:59+00:00"]),
+        (Tz::Europe__London, "2025-10-26T01:00:00", &["2025-10-26T00:00:00+00:00", "2025-10-26T01:00:00+00:00"]),
+        (Tz::Europe__London, "2025-10-26T01:30:00", &["2025-10-26T00:30:00+00:00", "2025-10-26T01:30:00+00:00"]),
+        (Tz::Europe__London, "2025-10-26T02:00:00", &["2025-10-26T02:00:00+00:00"]),
+    ];
+
+    #[test]
+    fn dst_transitions_match_golden_instants() {
+        for &(zone, wall, expected) in DST_GOLDEN {
+            let ndt = parse_input(wall).unwrap();
+            let utc = |dt: DateTime<Tz>| dt.with_timezone(&Utc).to_rfc3339();
+            let got: Vec<String> = match zone.from_local_datetime(&ndt) {
+                LocalResult::None => vec![],
+                LocalResult::Single(dt) => vec![utc(dt)],
+                LocalResult::Ambiguous(earliest, latest) => vec![utc(earliest), utc(latest)],
+            };
+            assert_eq!(got, expected, "{wall} in {zone}");
+
+            let converted = TimeConverter::new(zone, Tz::UTC).to_target(ndt);
+            match expected {
+                [] => assert_eq!(converted, Err(ConvertError::NonExistent { zone, time: ndt })),
+                [single] => assert_eq!(converted.unwrap().to_rfc3339(), *single),
+                _ => assert!(matches!(converted, Err(ConvertError::Ambiguous { .. }))),
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn conversions_round_trip_through_json() {
+        let input = parse("21:00 22-09-2025").unwrap();
+        let conversion = convert(input, Tz::Australia__Adelaide, Direction::ToUtc).unwrap();
+        let json = serde_json::to_string(&conversion).unwrap();
+        assert_eq!(
+            json,
+            r#"{"utc":"2025-09-22T11:30:00Z","local":"2025-09-22T21:00:00+09:30","zone":"Australia/Adelaide","direction":"ToUtc"}"#
+        );
+        assert_eq!(serde_json::from_str::<Conversion>(&json).unwrap(), conversion);
+        let moved = json.replace("+09:30", "+10:00");
+        assert!(serde_json::from_str::<Conversion>(&moved).is_err());
+
+        let error = parse("21:00 31-02-2025").unwrap_err();
+        let json = serde_json::to_string(&error).unwrap();
+        assert_eq!(serde_json::from_str::<ParseError>(&json).unwrap(), error);
+    }
+
+    #[test]
+    fn trace_records_the_year_rewrite() {
+        let (_, trace) = parse_traced("21:00 22-09-25", &ParseOptions::default()).unwrap();
+        assert_eq!(trace.format, "%H:%M %d-%m-%Y");
+        assert_eq!(trace.year_rewrite, Some((25, 2025)));
+    }
+
+    #[test]
+    fn strict_years_reject_two_digits() {
+        let strict = ParseOptions { strict_years: true, ..ParseOptions::default() };
+        for s in ["21:00 22-09-25", "22/09/25 9:00 PM", "22-09-25"] {
+            assert_eq!(parse_traced(s, &strict), Err(ParseError::TwoDigitYear("25".into())), "{s}");
+            assert!(parse_traced(s, &ParseOptions::default()).is_ok(), "{s}");
+        }
+        let (input, _) = parse_traced("21:00 22-09-2025", &strict).unwrap();
+        assert_eq!(input, Input::Naive(at(2025, 9, 22, 21, 0, 0)));
+        assert!(parse_traced("21:00 2025-09-22", &strict).is_ok());
+        assert!(parse_traced("21:00 22-09-0025", &strict).is_ok());
+    }
+
+    #[test]
+    fn date_command_output_parses() {
+        // As printed by `LC_ALL=C date -u -d @1758538800` and without -u
+        let utc = FixedOffset::east_opt(0).unwrap().with_ymd_and_hms(2025, 9, 22, 11, 0, 0).unwrap();
+        assert_eq!(parse("Mon Sep 22 11:00:00 UTC 2025"), Ok(Input::Instant(utc)));
+        let aest = FixedOffset::east_opt(10 * 3600).unwrap().with_ymd_and_hms(2025, 9, 22, 21, 0, 0).unwrap();
+        assert_eq!(parse("Mon Sep 22 21:00:00 AEST 2025"), Ok(Input::Instant(aest)));
+        assert_eq!(parse("Tue Sep  2 11:00:00 UTC 2025"), Ok(Input::Instant(utc - TimeDelta::days(20))));
+        // Without a zone it is a wall-clock time like the others
+        assert_eq!(parse("Mon Sep 22 11:00:00 2025"), Ok(Input::Naive(at(2025, 9, 22, 11, 0, 0))));
+        assert!(parse("Tue Sep 22 11:00:00 UTC 2025").is_err());
+    }
+
+    #[test]
+    fn four_digit_first_fields_are_years() {
+        let opts = ParseOptions::default();
+        for s in ["2025-09-22", "21:00 2025-09-22", "2025-09-22 21:00"] {
+            let (input, trace) = parse_traced(s, &opts).unwrap();
+            assert_eq!(input.naive().date(), NaiveDate::from_ymd_opt(2025, 9, 22).unwrap(), "{s}");
+            assert_eq!(trace.year_first, None, "{s}");
+        }
+        assert_eq!(parse_input("21:00 2025-13-05"), Err(ParseError::UnrecognizedFormat));
+        // Two-digit fields could go either way, so the other reading is kept
+        let (input, trace) = parse_traced("21:00 22-09-12", &opts).unwrap();
+        assert_eq!(input.naive().date(), NaiveDate::from_ymd_opt(2012, 9, 22).unwrap());
+        assert_eq!(trace.year_first, NaiveDate::from_ymd_opt(2022, 9, 12));
+        let (_, trace) = parse_traced("21:00 12-09-12", &opts).unwrap();
+        assert_eq!(trace.year_first, None);
+    }
+
+    #[test]
+    fn epoch_references_shift_the_zero() {
+        let utc = Utc.with_ymd_and_hms(2025, 9, 22, 11, 0, 0).unwrap();
+        let read = |s, epoch_reference| {
+            let opts = ParseOptions { epoch_reference, ..ParseOptions::default() };
+            parse_traced(s, &opts).map(|(input, _)| input)
+        };
+        assert_eq!(read("1758538800", EpochReference::Unix), Ok(Input::Instant(utc.fixed_offset())));
+        assert_eq!(read("811854000", EpochReference::J2000), Ok(Input::Instant(utc.fixed_offset())));
+        assert_eq!(read("134030124000000000", EpochReference::FileTime), Ok(Input::Instant(utc.fixed_offset())));
+        let filetime_zero = Utc.with_ymd_and_hms(1601, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(read("@0", EpochReference::FileTime), Ok(Input::Instant(filetime_zero.fixed_offset())));
+        // The unit scales J2000 counts but not FILETIME ticks
+        let opts = ParseOptions { epoch_unit: EpochUnit::Millis, epoch_reference: EpochReference::J2000, ..ParseOptions::default() };
+        assert_eq!(parse_traced("811854000000", &opts).unwrap().0, Input::Instant(utc.fixed_offset()));
+        for (reference, count) in [(EpochReference::Unix, 1758538800), (EpochReference::J2000, 811854000), (EpochReference::FileTime, 134030124000000000)] {
+            assert_eq!(reference.count(utc), count, "{reference:?}");
+        }
+        assert_eq!(EpochReference::J2000.count(Utc.with_ymd_and_hms(1999, 12, 31, 23, 59, 59).unwrap() + TimeDelta::milliseconds(500)), -1);
+        assert_eq!("filetime".parse(), Ok(EpochReference::FileTime));
+        assert!("ntp".parse::<EpochReference>().is_err());
+    }
+
+    #[test]
+    fn noon_and_midnight_name_their_times() {
+        assert_eq!(parse_input("noon 22-09-2025"), Ok(at(2025, 9, 22, 12, 0, 0)));
+        assert_eq!(parse_input("Midnight 22-09-2025"), Ok(at(2025, 9, 22, 0, 0, 0)));
+        assert_eq!(parse_input("22/09/2025 midnight"), Ok(at(2025, 9, 22, 0, 0, 0)));
+        let base = NaiveDate::from_ymd_opt(2025, 9, 22).unwrap();
+        let opts = ParseOptions { base_date: Some(base), ..ParseOptions::default() };
+        let read = |s| parse_traced(s, &opts).map(|(input, _)| input.naive());
+        assert_eq!(read("midnight tomorrow"), Ok(at(2025, 9, 23, 0, 0, 0)));
+        assert_eq!(read("noon"), Ok(at(2025, 9, 22, 12, 0, 0)));
+        assert_eq!(read("noon today"), Ok(at(2025, 9, 22, 12, 0, 0)));
+        assert!(parse_input("noonish 22-09-2025").is_err());
+    }
+
+    #[test]
+    fn basic_format_reads_back_ical() {
+        let opts = ParseOptions::default();
+        let (input, trace) = parse_traced("20250922T210000", &opts).unwrap();
+        assert_eq!(input, Input::Naive(at(2025, 9, 22, 21, 0, 0)));
+        assert_eq!(trace.format, "%Y%m%dT%H%M%S");
+        assert_eq!(parse_input("20250922210000"), Ok(at(2025, 9, 22, 21, 0, 0)));
+        let utc = Utc.with_ymd_and_hms(2025, 9, 22, 11, 0, 0).unwrap();
+        assert_eq!(parse("20250922T110000Z"), Ok(Input::Instant(utc.fixed_offset())));
+        // In milliseconds fourteen digits are an epoch again
+        let ms = ParseOptions { epoch_unit: EpochUnit::Millis, ..ParseOptions::default() };
+        assert_eq!(parse_traced("20250922210000", &ms).unwrap().1.format, "epoch");
+        for s in ["20251322T210000", "20250922T2100", "2025092T210000", "20250922210000Z", "20250922T250000"] {
+            assert!(parse(s).is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn lines_convert_one_at_a_time() {
+        let sydney = TimeConverter::new(Tz::Australia__Sydney, Tz::UTC);
+        let text = "21:00 22-09-2025\r\n   \r\n02:30 05-10-2025\r\n1970-01-01T10:00:00+10:00\r\n";
+        let results: Vec<_> = convert_lines(text.as_bytes(), &sydney, &ParseOptions::default()).collect();
+        assert_eq!(results.len(), 3);
+        let first = results[0].as_ref().unwrap();
+        assert_eq!((first.utc.to_rfc3339(), first.direction), ("2025-09-22T11:00:00+00:00".into(), Direction::ToUtc));
+        // Skipped by the DST change, and still numbered past the blank line
+        assert!(matches!(results[1], Err(LineError::Convert { line: 3, error: ConvertError::NonExistent { .. } })));
+        assert_eq!(results[2].as_ref().unwrap().utc.timestamp(), 0);
+
+        let outward = TimeConverter::new(Tz::UTC, Tz::Asia__Tokyo);
+        let c = convert_lines("11:00 22-09-2025".as_bytes(), &outward, &ParseOptions::default()).next().unwrap().unwrap();
+        assert_eq!((c.local.to_rfc3339(), c.direction), ("2025-09-22T20:00:00+09:00".into(), Direction::ToLocal));
+        let err = convert_lines("25:00".as_bytes(), &outward, &ParseOptions::default()).next().unwrap().unwrap_err();
+        assert!(err.to_string().starts_with("line 1: "));
+    }
+
+    #[test]
+    fn assumed_years_move_bare_times() {
+        let base = NaiveDate::from_ymd_opt(2025, 12, 30).unwrap();
+        let opts = ParseOptions { base_date: Some(base), assume_year: Some(2026), ..ParseOptions::default() };
+        let read = |s, opts: &ParseOptions| parse_traced(s, opts).map(|(input, _)| input.naive());
+        assert_eq!(read("21:00", &opts), Ok(at(2026, 12, 30, 21, 0, 0)));
+        // Only the bare time moves
+        assert_eq!(read("21:00 tomorrow", &opts), Ok(at(2025, 12, 31, 21, 0, 0)));
+        assert_eq!(read("21:00 01-01-2026", &opts), Ok(at(2026, 1, 1, 21, 0, 0)));
+        let leap = ParseOptions { base_date: NaiveDate::from_ymd_opt(2024, 2, 29), ..opts };
+        assert_eq!(read("09:00", &leap), Err(ParseError::InvalidDate { day: 29, month: 2, year: 2026 }));
+    }
+
+    #[test]
+    fn every_field_order_is_offered() {
+        let opts = ParseOptions::default();
+        let readings = parse_all("01/02/03 21:00", &opts);
+        assert_eq!(
+            readings,
+            [
+                (at(2003, 2, 1, 21, 0, 0), "%d/%m/%Y %H:%M".to_string()),
+                (at(2003, 1, 2, 21, 0, 0), "%m/%d/%Y %H:%M".to_string()),
+                (at(2001, 2, 3, 21, 0, 0), "%Y/%m/%d %H:%M".to_string()),
+            ]
+        );
+        // No 13th month, no year-first reading of a four-digit year, and a
+        // swap that changes nothing is not offered twice
+        assert_eq!(parse_all("21:00 13-02-2025", &opts).len(), 1);
+        assert_eq!(parse_all("21:00 02-03-2025", &opts)[1], (at(2025, 2, 3, 21, 0, 0), "%H:%M %m-%d-%Y".to_string()));
+        assert_eq!(parse_all("21:00 05-05-05", &opts).len(), 1);
+        assert_eq!(parse_all("2025-01-02", &opts).len(), 1);
+        assert_eq!(parse_all("@0", &opts), [(at(1970, 1, 1, 0, 0, 0), "epoch".to_string())]);
+        assert!(parse_all("tomorrow", &opts).is_empty());
+    }
+
+    #[test]
+    fn military_letters_fix_the_offset() {
+        let at_offset = |hours: i32, h| {
+            let offset = FixedOffset::east_opt(hours * 3600).unwrap();
+            Ok(Input::Instant(offset.with_ymd_and_hms(2025, 9, 22, h, 0, 0).unwrap()))
+        };
+        assert_eq!(parse("21:00Z 22-09-2025"), at_offset(0, 21));
+        assert_eq!(parse("21:00A 22-09-2025"), at_offset(1, 21));
+        assert_eq!(parse("21:00K 22-09-2025"), at_offset(10, 21));
+        assert_eq!(parse("22-09-2025 09:00:00Y"), at_offset(-12, 9));
+        assert!(parse("21:00J 22-09-2025").is_err());
+        assert!(parse("21:00k 22-09-2025").is_err());
+        let utc = parse("21:00K 22-09-2025").unwrap();
+        assert_eq!(convert(utc, Tz::UTC, Direction::ToUtc).unwrap().utc.to_rfc3339(), "2025-09-22T11:00:00+00:00");
+    }
+
+    #[test]
+    fn the_format_cache_never_changes_a_reading() {
+        // Runs of one layout, then lines a remembered pattern would misread
+        // or that earlier branches must still claim
+        let lines = [
+            "21:00 22-09-2025",
+            "09:30 23-09-2025",
+            "9:30 23-09-2025",
+            "21:00 22-09-25",
+            "21:00 22-09-70",
+            "21:00 01-01-0025",
+            "21:00 AEST 22-09-2025",
+            "21:00  22-09-2025",
+            "21:00:30 22-09-2025",
+            "21:00:30.250 22-09-2025",
+            "21:00:60 30-06-2015",
+            "09:00 PM 22/09/2025",
+            "09:00 AM 22/09/2025",
+            "2025-09-22 21:00",
+            "2025-09-22 21:00",
+            "21:00 2025-09-22",
+            "22-09-2025 21:00",
+            "22-09-2025 21:00Z",
+            "1758538800000",
+            "21:00",
+            "21:00 friday",
+            "not a time",
+            "22-09-2025 21:00",
+        ];
+        let text = lines.join("\n");
+        let base = ParseOptions { base_date: Some(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()), ..ParseOptions::default() };
+        let options = [
+            ParseOptions::default(),
+            ParseOptions { year_pivot: 80, past_weekdays: true, epoch_unit: EpochUnit::Millis, ..base },
+            ParseOptions { assume_year: Some(2030), strict_years: true, today_zone: Tz::Asia__Tokyo, ..base },
+        ];
+        for opts in options {
+            let cached: Vec<_> = parse_lines(text.as_bytes(), &opts).collect();
+            assert_eq!(cached.len(), lines.len());
+            for (line, result) in lines.iter().zip(cached) {
+                let result = match result {
+                    Ok(parsed) => Ok((parsed.input, parsed.trace)),
+                    Err(LineError::Parse { error, .. }) => Err(error),
+                    Err(e) => panic!("{e}"),
+                };
+                assert_eq!(result, parse_traced(line, &opts), "{line}");
+            }
+        }
+    }
+}
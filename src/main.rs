@@ -1,117 +1,1290 @@
+mod cli;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod config;
+mod csv;
+mod output;
+
 use std::env;
-use std::io::{self, Write};
-use chrono::{NaiveDateTime, NaiveDate, NaiveTime, Datelike, Utc, TimeZone, LocalResult};
+use std::fs::{File, OpenOptions};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, ErrorKind, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use chrono::{DateTime, Datelike, Days, DurationRound, NaiveDateTime, Offset, SubsecRound, TimeDelta, Utc, TimeZone};
 use chrono_tz::Australia::Brisbane;
+use chrono_tz::Tz;
+use utc_time::{abbrev, century_candidates, closest_zone, parse_all, parse_lines, parse_traced, split_range, transitions_in_year, zone_for_city, Conversion, ConvertError, Direction, Input, LineError, ParsedLine, Trace};
+
+use cli::{choice_for, direction_from_choice, BareTz, Cli, Color, HELP};
+use config::Config;
+use output::{bizdays_line, business_hours_line, calendar_line, candidate, display_format, dst_status_line, format_list, humanize, json_ambiguous, json_result, json_string, offset_line, offset_table, since_midnight_line, signed_span, span, transition_line, zone_gap_line, zone_gap_seconds, Format, Output};
+
+// --no-newline, set once at startup. Lines then carry their newline in front
+// rather than behind, so the last one written has none
+static NO_NEWLINE: AtomicBool = AtomicBool::new(false);
+static WROTE_LINE: AtomicBool = AtomicBool::new(false);
 
-fn usage() {
-    eprintln!("Usage: utc_time HH:MM");
-    eprintln!("Usage: utc_time HH:MM dd-mm-yy|YYYY");
-    std::process::exit(1);
+// println! for results, honouring --no-newline; see `emit` for its errors
+macro_rules! out {
+    ($($arg:tt)*) => {
+        emit(format_args!($($arg)*))
+    };
 }
 
-fn map_two_digit_year(y: i32) -> i32 {
-    if (0..=68).contains(&y) { 2000 + y }
-    else if (69..=99).contains(&y) { 1900 + y }
-    else { y }
+fn write_line(line: fmt::Arguments) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    if !NO_NEWLINE.load(Ordering::Relaxed) {
+        return stdout.write_fmt(line).and_then(|()| stdout.write_all(b"\n"));
+    }
+    if WROTE_LINE.swap(true, Ordering::Relaxed) {
+        stdout.write_all(b"\n")?;
+    }
+    stdout.write_fmt(line).and_then(|()| stdout.flush())
 }
 
-fn fix_two_digit_year(ndt: NaiveDateTime) -> NaiveDateTime {
-    let y = ndt.date().year();
-    if (0..=99).contains(&y) {
-        let ny = map_two_digit_year(y);
-        let m = ndt.date().month();
-        let d = ndt.date().day();
-        let t = ndt.time();
-        let date = NaiveDate::from_ymd_opt(ny, m, d).expect("mapped year should be valid");
-        NaiveDateTime::new(date, t)
-    } else {
-        ndt
+/// Process exit codes. These are part of the CLI contract for wrapper
+/// scripts, so existing values must never be renumbered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExitCode {
+    Usage = 1,
+    TooManyArgs = 2,
+    ParseError = 3,
+    NonExistentLocal = 4,
+    Ambiguous = 5,
+    BadChoice = 6,
+    UnknownTimezone = 7,
+    InputFile = 8,
+    NoChoice = 9,
+    LogFile = 10,
+    Stdout = 11,
+}
+
+impl ExitCode {
+    // A failure with this code, reported as `msg` or, under --json, a JSON object
+    fn fail(self, msg: &str, json: bool) -> AppError {
+        AppError::Fail { code: self, message: msg.to_string(), json }
     }
 }
 
-fn parse_input(s: &str) -> Result<NaiveDateTime, String> {
-    if let Ok(t) = NaiveTime::parse_from_str(s, "%H:%M") {
-        let today_bne = Utc::now().with_timezone(&Brisbane).date_naive();
-        return Ok(NaiveDateTime::new(today_bne, t));
+/// Why a run stopped early. [`AppError::report`] is the one place these turn
+/// into output on stderr and a process exit code.
+#[derive(Debug)]
+enum AppError {
+    /// `message` on stderr, or under --json an object with the code, then exit
+    /// with `code`.
+    Fail { code: ExitCode, message: String, json: bool },
+    /// A command line that does not add up: the message, if any, then the
+    /// usage summary, exiting 1.
+    Usage(Option<String>),
+    /// Already reported, as batch modes do per line or --json does for an
+    /// ambiguous time; only the exit code is left.
+    Reported(ExitCode),
+    /// stdout was closed, as in `--stdin | head -1`. Nobody wants the rest,
+    /// so the run ends quietly with success.
+    ClosedPipe,
+}
+
+impl AppError {
+    fn exit_code(&self) -> u8 {
+        match self {
+            AppError::Fail { code, .. } | AppError::Reported(code) => *code as u8,
+            AppError::Usage(_) => ExitCode::Usage as u8,
+            AppError::ClosedPipe => 0,
+        }
+    }
+
+    fn report(self) -> std::process::ExitCode {
+        let code = self.exit_code();
+        match self {
+            AppError::Fail { code, message, json: true } => {
+                eprintln!("{{\"error_code\": {}, \"message\": {}}}", code as i32, json_string(&message));
+            }
+            AppError::Fail { message, .. } => eprintln!("{message}"),
+            AppError::Usage(message) => {
+                if let Some(message) = message {
+                    eprintln!("{message}");
+                }
+                eprintln!("Usage: utc_time [OPTIONS] HH:MM[:SS]");
+                eprintln!("Usage: utc_time [OPTIONS] HH:MM[:SS] dd-mm-yy|YYYY");
+                eprintln!("Usage: utc_time [OPTIONS] [@]EPOCH|RFC3339|now");
+                eprintln!("Usage: utc_time [OPTIONS] --stdin");
+                eprintln!("Run with --help for every format, option and exit code.");
+            }
+            AppError::Reported(_) | AppError::ClosedPipe => {}
+        }
+        std::process::ExitCode::from(code)
     }
+}
 
-    let formats = [
-        "%H:%M %d-%m-%Y", // 21:00 22-09-2025
-        "%H:%M %d-%m-%y", // 21:00 22-09-25
-        "%H:%M %d/%m/%Y", // 21:00 22/09/2025
-        "%H:%M %d/%m/%y", // 21:00 22/09/25
-    ];
+// The first failure of a batch, if any, as the run's result
+fn batch_status(status: Option<ExitCode>) -> Result<(), AppError> {
+    status.map_or(Ok(()), |code| Err(AppError::Reported(code)))
+}
 
-    for fmt in formats {
-        if let Ok(ndt) = NaiveDateTime::parse_from_str(s, fmt) {
-            return Ok(fix_two_digit_year(ndt));
+// Which instant to pick when a local time occurs twice
+#[derive(Clone, Copy, Debug, Default)]
+enum Ambiguity {
+    #[default]
+    Reject,
+    Earliest,
+    Latest,
+}
+
+// How to settle local times that a DST change makes ambiguous or skips
+#[derive(Clone, Copy, Debug, Default)]
+struct Resolve {
+    ambiguity: Ambiguity,
+    fold_forward: bool,
+}
+
+// The machine's zone: $TZ when it holds an IANA name, else the OS setting
+fn system_tz() -> Option<Tz> {
+    env::var("TZ")
+        .ok()
+        .and_then(|name| name.trim_start_matches(':').parse().ok())
+        .or_else(|| iana_time_zone::get_timezone().ok()?.parse().ok())
+}
+
+// `local`, a common abbreviation such as AEST, an IANA name, or a city on its
+// own such as Brisbane. Abbreviations go first so `EST` means New York rather
+// than the fixed IANA `EST` zone
+fn lookup_tz(name: &str) -> Result<Tz, String> {
+    if name.eq_ignore_ascii_case("local") {
+        return system_tz().ok_or_else(|| "Cannot determine the system timezone; pass an IANA name instead of 'local'".to_string());
+    }
+    match abbrev::zones_for(name) {
+        [tz] => Ok(*tz),
+        [] => name.parse::<Tz>().ok().or_else(|| zone_for_city(name)).ok_or_else(|| match closest_zone(name) {
+            Some(tz) => format!("Unknown timezone '{name}'; did you mean {tz}?"),
+            None => format!("Unknown timezone '{name}'; use an IANA name like Europe/Berlin"),
+        }),
+        zones => {
+            let names: Vec<&str> = zones.iter().map(|tz| tz.name()).collect();
+            Err(format!("'{name}' is ambiguous; use one of: {}", names.join(", ")))
         }
     }
+}
 
-    Err("Unrecognized format. Try: HH:MM or HH:MM dd-mm-yy|yyyy or HH:MM dd/mm/yy|yyyy".into())
+fn parse_tz(name: &str, json: bool) -> Result<Tz, AppError> {
+    lookup_tz(name).map_err(|msg| ExitCode::UnknownTimezone.fail(&msg, json))
 }
 
-fn main() {
-    // Accept: one arg (possibly quoted) or two args (time and date)
-    let args: Vec<String> = env::args().skip(1).collect();
-    if args.is_empty() {
-        usage()
+// --tz-file: one zone per line; `#` starts a comment and blank lines are skipped
+fn read_tz_file(path: &str, json: bool) -> Result<Vec<Tz>, AppError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| ExitCode::InputFile.fail(&format!("Cannot read {path}: {e}"), json))?;
+    let mut zones = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let name = line.split('#').next().unwrap_or("").trim();
+        if name.is_empty() {
+            continue;
+        }
+        match lookup_tz(name) {
+            Ok(tz) => zones.push(tz),
+            Err(msg) => return Err(ExitCode::UnknownTimezone.fail(&format!("{path}:{}: {msg}", index + 1), json)),
+        }
     }
-    let input = match args.len() {
-        1 => args[0].clone(),
-        2 => format!("{} {}", args[0], args[1]),
-        _ => {
-            eprintln!("Too many arguments; pass either one quoted string or two separate args");
-            std::process::exit(2);
+    if zones.is_empty() {
+        return Err(ExitCode::UnknownTimezone.fail(&format!("No timezone listed in {path}"), json));
+    }
+    Ok(zones)
+}
+
+fn parse_zones(list: &str, json: bool) -> Result<Vec<Tz>, AppError> {
+    let zones: Vec<Tz> = list
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| parse_tz(name, json))
+        .collect::<Result<_, _>>()?;
+    if zones.is_empty() {
+        return Err(ExitCode::UnknownTimezone.fail(&format!("No timezone given in '{list}'"), json));
+    }
+    Ok(zones)
+}
+
+// Shown by --world unless --tz or --tz-file names other zones
+const WORLD_ZONES: [Tz; 9] = [
+    Tz::America__Los_Angeles,
+    Tz::America__New_York,
+    Tz::UTC,
+    Tz::Europe__London,
+    Tz::Europe__Berlin,
+    Tz::Asia__Kolkata,
+    Tz::Asia__Tokyo,
+    Tz::Australia__Brisbane,
+    Tz::Australia__Sydney,
+];
+
+// --chain: the hops in order; a chain needs somewhere to go
+fn parse_chain(list: &str, json: bool) -> Result<Vec<Tz>, AppError> {
+    let hops: Vec<Tz> = list.split('>').map(|name| parse_tz(name.trim(), json)).collect::<Result<_, _>>()?;
+    if hops.len() < 2 {
+        let msg = format!("--chain needs at least two zones, e.g. Asia/Tokyo>Europe/London; got '{list}'");
+        return Err(ExitCode::UnknownTimezone.fail(&msg, json));
+    }
+    Ok(hops)
+}
+
+// One numbered line per hop, all the same instant, names padded into a column
+fn run_chain(cli: &Cli, raw: &str, input: Input, hops: &[Tz], output: &Output) -> Result<(), AppError> {
+    let direction = cli.direction.unwrap_or(Direction::ToUtc);
+    fail_ambiguous_json(cli, input, direction, hops[0])?;
+    let c = convert(input, direction, hops[0], cli).map_err(|(code, msg)| code.fail(&msg, cli.json))?;
+    log_conversion(cli, raw, c.utc, c.local)?;
+    let utc = c.utc;
+    if cli.json {
+        let results: Vec<String> = hops.iter().map(|z| json_result(utc, utc.with_timezone(z), *z)).collect();
+        out!("[{}]", results.join(", "))?;
+        return Ok(());
+    }
+    let width = hops.iter().map(|z| z.name().len()).max().unwrap_or(0);
+    for (n, z) in hops.iter().enumerate() {
+        out!("{}. {:<width$}  {}", n + 1, z.name(), output.local(utc.with_timezone(z)))?;
+    }
+    Ok(())
+}
+
+// One line per zone for the current instant, names padded into a column
+fn world_clock(zones: &[Tz], output: &Output, json: bool) -> Result<(), AppError> {
+    let now = Utc::now().trunc_subsecs(0);
+    if json {
+        let results: Vec<String> = zones.iter().map(|z| json_result(now, now.with_timezone(z), *z)).collect();
+        return out!("[{}]", results.join(", "));
+    }
+    let width = zones.iter().map(|z| z.name().len()).max().unwrap_or(0);
+    for z in zones {
+        out!("{:<width$}  {}", z.name(), output.local(now.with_timezone(z)))?;
+    }
+    Ok(())
+}
+
+// Prints every known IANA name, optionally keeping only case-insensitive matches
+fn list_timezones(filter: Option<&str>) -> Result<(), AppError> {
+    let filter = filter.map(str::to_lowercase);
+    for tz in chrono_tz::TZ_VARIANTS {
+        if filter.as_ref().is_none_or(|f| tz.name().to_lowercase().contains(f)) {
+            out!("{}", tz.name())?;
         }
-    };
+    }
+    Ok(())
+}
+
+#[cfg(feature = "clipboard")]
+fn read_clipboard(json: bool) -> Result<String, AppError> {
+    clipboard::read().map(|text| text.trim().to_string()).map_err(|msg| ExitCode::InputFile.fail(&msg, json))
+}
 
-    let ndt = match parse_input(&input) {
-        Ok(ndt) => ndt,
-        Err(e) => {
-            eprintln!("Parse error: {e}");
-            std::process::exit(3);
+#[cfg(not(feature = "clipboard"))]
+fn read_clipboard(_json: bool) -> Result<String, AppError> {
+    Err(AppError::Usage(Some("--from-clipboard needs a build with --features clipboard".to_string())))
+}
+
+// Shows the menu on stderr, keeping stdout for results, and reads the answer:
+// `None` when stdin closes first, and `default`'s choice for a bare Enter
+fn prompt_choice(input_tz: Tz, tz: Tz, default: Option<Direction>) -> io::Result<Option<String>> {
+    eprintln!("Select conversion:");
+    eprintln!("  1) {input_tz} -> UTC");
+    eprintln!("  2) UTC -> {tz}");
+    match default {
+        Some(default) => eprint!("Choice [1/2, Enter for {}]: ", choice_for(default)),
+        None => eprint!("Choice [1/2]: "),
+    }
+
+    let mut choice = String::new();
+    Ok(match io::stdin().read_line(&mut choice)? {
+        0 => None,
+        _ => match (choice.trim(), default) {
+            ("", Some(default)) => Some(choice_for(default).to_string()),
+            (answer, _) => Some(answer.to_string()),
+        },
+    })
+}
+
+// Choice 1 reads the input, and reports it, in --input-tz
+fn reading_zone(input_tz: Option<Tz>, tz: Tz, direction: Direction) -> Tz {
+    match input_tz {
+        Some(zone) if direction == Direction::ToUtc => zone,
+        _ => tz,
+    }
+}
+
+// A choice that could not be read at all counts as none given
+fn choice_or_fail(choice: io::Result<Option<String>>, json: bool) -> Result<Option<String>, AppError> {
+    choice.map_err(|e| ExitCode::NoChoice.fail(&format!("Cannot read the choice: {e}"), json))
+}
+
+// Resolves the input to both sides of the conversion, shifted by --add and
+// --subtract, or an exit code and message
+fn convert(input: Input, direction: Direction, tz: Tz, cli: &Cli) -> Result<Conversion, (ExitCode, String)> {
+    resolve(input, direction, tz, cli.resolve)?
+        .shifted(cli.shift)
+        .ok_or_else(|| (ExitCode::ParseError, "The shifted time is out of range".to_string()))
+}
+
+// Like `convert` without the shift
+fn resolve(input: Input, direction: Direction, tz: Tz, resolve: Resolve) -> Result<Conversion, (ExitCode, String)> {
+    let local = match utc_time::convert(input, tz, direction) {
+        Ok(conversion) => conversion.local,
+        Err(ConvertError::NonExistent { time, .. }) if resolve.fold_forward => {
+            // Read the wall-clock with the offset from before the gap, which
+            // lands the same distance past it (02:30 in a 02:00-03:00 gap -> 03:30)
+            let before = tz.offset_from_utc_datetime(&(time - TimeDelta::days(1))).fix();
+            Utc.from_utc_datetime(&(time - before)).with_timezone(&tz)
         }
+        Err(ConvertError::NonExistent { .. }) => {
+            return Err((ExitCode::NonExistentLocal, format!(
+                "Non-existent local time in {tz} (skipped by a DST change); pass --fold-forward to shift past the gap"
+            )));
+        }
+        Err(e @ ConvertError::OutOfRange { .. }) => return Err((ExitCode::ParseError, e.to_string())),
+        Err(ConvertError::Ambiguous { earliest, latest }) => match resolve.ambiguity {
+            Ambiguity::Earliest => earliest,
+            Ambiguity::Latest => latest,
+            Ambiguity::Reject => {
+                return Err((ExitCode::Ambiguous, format!(
+                    "Ambiguous local time in {tz} (repeated by a DST change): {} or {}; pass --earliest or --latest",
+                    candidate(earliest),
+                    candidate(latest),
+                )));
+            }
+        },
     };
+    Ok(Conversion { utc: local.with_timezone(&Utc), local, direction })
+}
 
-    println!("Select conversion:");
-    println!("  1) AEST (Brisbane) -> UTC");
-    println!("  2) UTC -> AEST (Brisbane)");
-    print!("Choice [1/2]: ");
-    io::stdout().flush().expect("flush stdout");
+// With --json a time a DST change repeats lists both readings on stdout,
+// still exiting 5, unless --earliest or --latest already settles it
+fn fail_ambiguous_json(cli: &Cli, input: Input, direction: Direction, tz: Tz) -> Result<(), AppError> {
+    if cli.json
+        && matches!(cli.resolve.ambiguity, Ambiguity::Reject)
+        && let Err(ConvertError::Ambiguous { earliest, latest }) = utc_time::convert(input, tz, direction)
+    {
+        out!("{}", json_ambiguous(earliest, latest))?;
+        return Err(AppError::Reported(ExitCode::Ambiguous));
+    }
+    Ok(())
+}
 
-    let mut choice = String::new();
-    io::stdin().read_line(&mut choice).expect("read choice");
-    let choice = choice.trim();
-
-    match choice {
-        "1" => {
-            // Treat input as Brisbane local -> convert to UTC
-            match Brisbane.from_local_datetime(&ndt) {
-                LocalResult::Single(local_dt) => {
-                    let utc = local_dt.with_timezone(&Utc);
-                    println!("UTC: {}", utc.to_rfc3339());
-                    println!("Brisbane: {}", local_dt.format("%Y-%m-%d %H:%M %Z"));
+// --relative-to: the signed span from the reference to `utc`. The reference
+// is read like the input, but --add and --subtract leave it where it is
+fn relative_line(cli: &Cli, reference: &str, utc: DateTime<Utc>, direction: Direction, tz: Tz) -> Result<String, AppError> {
+    let reference = read_input(reference, cli)
+        .map_err(|e| (ExitCode::ParseError, format!("Parse error in --relative-to: {e}")))
+        .and_then(|input| resolve(input, direction, tz, cli.resolve))
+        .map_err(|(code, msg)| code.fail(&msg, cli.json))?;
+    Ok(format!("Relative: {}", signed_span(utc - reference.utc)))
+}
+
+// Rounds before any zone is applied, so offsets never shift the boundary
+fn round_input(input: Input, step: TimeDelta) -> Result<Input, String> {
+    let rounded = match input {
+        Input::Naive(ndt) => ndt.duration_round(step).map(Input::Naive),
+        Input::Instant(dt) => dt.duration_round(step).map(Input::Instant),
+    };
+    rounded.map_err(|e| format!("Cannot round {}: {e}", input.naive()))
+}
+
+// Parses and rounds one input, logging the parse path under --verbose
+fn read_input(s: &str, cli: &Cli) -> Result<Input, String> {
+    let (input, trace) = parse_traced(s, &cli.parse_opts).map_err(|e| e.to_string())?;
+    prepare_input(input, &trace, cli)
+}
+
+// The notes on how `input` was parsed, then --round
+fn prepare_input(input: Input, trace: &Trace, cli: &Cli) -> Result<Input, String> {
+    if cli.verbose {
+        eprintln!("matched format: {}", trace.format);
+        eprintln!("parsed as: {}", trace.parsed);
+    }
+    // Century inference is convenient but easy to miss, so say so unless told
+    // not to or both centuries are about to be shown
+    if let Some((from, to)) = trace.year_rewrite
+        && !cli.both_centuries
+        && (cli.verbose || !(cli.quiet || cli.json))
+    {
+        eprintln!("note: interpreted year {from} as {to} (use --year-pivot or a 4-digit year to override)");
+    }
+    // Either side of the pivot, a slip of one year moves the date a century
+    let pivot = cli.parse_opts.year_pivot;
+    if let Some((from, _)) = trace.year_rewrite
+        && cli.verbose
+        && pivot < 99
+        && (pivot..=pivot + 1).contains(&from)
+    {
+        eprintln!("note: {from:02} is at the century cutoff: {pivot:02} and below are 20xx, {:02} and above 19xx", pivot + 1);
+    }
+    if let Some(date) = trace.year_first
+        && !cli.all_readings
+        && (cli.verbose || !(cli.quiet || cli.json))
+    {
+        eprintln!("warning: read the date day first; write {date} if you meant year first");
+    }
+    match cli.round {
+        Some(step) => round_input(input, step),
+        None => Ok(input),
+    }
+}
+
+// --log-file: opened for each entry, so nothing is held between conversions
+fn open_log(path: &str, json: bool) -> Result<File, AppError> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| ExitCode::LogFile.fail(&format!("Cannot open log file {path}: {e}"), json))
+}
+
+// Appends one tab-separated line: when the run happened, the input as typed,
+// then the instant in UTC and in `local`'s zone. A no-op without --log-file
+fn log_conversion(cli: &Cli, input: &str, utc: DateTime<Utc>, local: DateTime<Tz>) -> Result<(), AppError> {
+    let Some(path) = cli.log_file.as_deref() else { return Ok(()) };
+    let run = Utc::now().trunc_subsecs(0).to_rfc3339();
+    let line = format!("{run}\t{input}\t{}\t{}\t{}\n", utc.to_rfc3339(), local.to_rfc3339(), local.timezone());
+    open_log(path, cli.json)?
+        .write_all(line.as_bytes())
+        .map_err(|e| ExitCode::LogFile.fail(&format!("Cannot write log file {path}: {e}"), cli.json))
+}
+
+// Behind `out!`
+fn emit(line: fmt::Arguments) -> Result<(), AppError> {
+    written(write_line(line))
+}
+
+// --help and --format-list, printed as they are whatever --no-newline says
+fn emit_text(text: &str) -> Result<(), AppError> {
+    written(io::stdout().lock().write_all(text.as_bytes()))
+}
+
+// A closed pipe, as in `--list-timezones | head -1`, means nobody wants the
+// rest, so it ends the run quietly; any other failure, such as a full disk,
+// ends it with exit code 11
+fn written(result: io::Result<()>) -> Result<(), AppError> {
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => Err(AppError::ClosedPipe),
+        Err(e) => Err(ExitCode::Stdout.fail(&format!("cannot write to stdout: {e}"), false)),
+    }
+}
+
+// Converts each line independently; fails with the first failure's exit code.
+// `path` names the input file, if any, in error messages, and also gathers
+// --json results into one array instead of printing one object per line.
+// --group-by-date holds everything back until the input ends, failures too
+fn run_batch(cli: &Cli, lines: impl BufRead, path: Option<&str>, direction: Direction, tz: Tz, output: &Output) -> Result<(), AppError> {
+    let mut status = None;
+    let mut results = Vec::new();
+    let mut grouped = Vec::new();
+    let mut failures = Vec::new();
+    // Trimmed and without blank lines, so CRLF files read like any other
+    for parsed in parse_lines(lines, &cli.parse_opts) {
+        let (n, result) = match parsed {
+            Ok(ParsedLine { line, text, input, trace }) => {
+                let result = prepare_input(input, &trace, cli)
+                    .map_err(|e| (ExitCode::ParseError, format!("Parse error: {e}")))
+                    .and_then(|input| convert(input, direction, tz, cli));
+                (line, result.map(|c| (text, c)))
+            }
+            Err(LineError::Parse { line, error }) => (line, Err((ExitCode::ParseError, format!("Parse error: {error}")))),
+            Err(e) => return Err(ExitCode::InputFile.fail(&format!("{}: {e}", path.unwrap_or("stdin")), cli.json)),
+        };
+        if let Ok((line, c)) = &result {
+            log_conversion(cli, line, c.utc, c.local)?;
+        }
+        match result {
+            Ok((line, c)) if cli.group_by_date => grouped.push((line, c)),
+            Ok((line, c)) if cli.json && path.is_some() => results.push(echo(cli, &line, json_result(c.utc, c.local, tz))),
+            Ok((line, c)) if cli.json => out!("{}", echo(cli, &line, json_result(c.utc, c.local, tz)))?,
+            Ok((line, c)) if direction == Direction::ToUtc => out!("{}", echo(cli, &line, format!("UTC: {}", output.utc(c.utc))))?,
+            Ok((line, c)) => out!("{}", echo(cli, &line, format!("{tz}: {}", output.local(c.local))))?,
+            Err((code, msg)) => {
+                let report = match (cli.json, path) {
+                    (true, Some(path)) => format!(
+                        "{{\"path\": {}, \"line\": {n}, \"error_code\": {}, \"message\": {}}}",
+                        json_string(path),
+                        code as i32,
+                        json_string(&msg),
+                    ),
+                    (true, None) => format!(
+                        "{{\"line\": {n}, \"error_code\": {}, \"message\": {}}}",
+                        code as i32,
+                        json_string(&msg),
+                    ),
+                    (false, Some(path)) => format!("{path}:{n}: {msg}"),
+                    (false, None) => format!("line {n}: {msg}"),
+                };
+                if cli.group_by_date {
+                    failures.push(report);
+                } else {
+                    eprintln!("{report}");
                 }
-                LocalResult::None => {
-                    eprintln!("Non-existent local time in Brisbane (unexpected without DST)");
-                    std::process::exit(4);
+                status.get_or_insert(code);
+            }
+        }
+    }
+    if cli.group_by_date {
+        print_by_date(cli, grouped, direction, tz, output)?;
+        failures.iter().for_each(|report| eprintln!("{report}"));
+    } else if cli.json && path.is_some() {
+        out!("[{}]", results.join(", "))?;
+    }
+    batch_status(status)
+}
+
+// --echo-input: a batch result led by the line it came from, or as JSON
+// with that line as its first field
+fn echo(cli: &Cli, line: &str, result: String) -> String {
+    match (cli.echo_input, cli.json) {
+        (false, _) => result,
+        (true, true) => format!("{{\"input\": {}, {}", json_string(line), &result[1..]),
+        (true, false) => format!("{line} -> {result}"),
+    }
+}
+
+// --group-by-date: the results, each with the line it came from, in time
+// order under a heading for each date on the target side, which is UTC for
+// --to-utc and the zone for --to-local. Equal instants keep their input order
+fn print_by_date(cli: &Cli, mut conversions: Vec<(String, Conversion)>, direction: Direction, tz: Tz, output: &Output) -> Result<(), AppError> {
+    conversions.sort_by_key(|(_, c)| c.utc);
+    let date = |(_, c): &(String, Conversion)| if direction == Direction::ToUtc { c.utc.date_naive() } else { c.local.date_naive() };
+    let groups = conversions.chunk_by(|a, b| date(a) == date(b));
+    if cli.json {
+        let groups: Vec<String> = groups
+            .map(|group| {
+                let results: Vec<String> = group.iter().map(|(line, c)| echo(cli, line, json_result(c.utc, c.local, tz))).collect();
+                format!("{{\"date\": {}, \"results\": [{}]}}", json_string(&date(&group[0]).to_string()), results.join(", "))
+            })
+            .collect();
+        return out!("[{}]", groups.join(", "));
+    }
+    for group in groups {
+        out!("{}", date(&group[0]).format("%a %Y-%m-%d"))?;
+        for (line, c) in group {
+            let result = match direction {
+                Direction::ToUtc => format!("UTC: {}", output.utc(c.utc)),
+                Direction::ToLocal => format!("{tz}: {}", output.local(c.local)),
+            };
+            out!("  {}", echo(cli, line, result))?;
+        }
+    }
+    Ok(())
+}
+
+// --csv: converts field `column` (1-based) of each record, replacing it or,
+// with --append-column, adding the result as a new last field. Records that
+// fail pass through unchanged with a note on stderr, except a first record
+// that fails, which is taken for a header. Fails with the first failure's code
+fn run_csv(cli: &Cli, lines: impl BufRead, column: usize, direction: Direction, tz: Tz, output: &Output) -> Result<(), AppError> {
+    let mut status = None;
+    for (i, line) in lines.lines().enumerate() {
+        let n = i + 1;
+        let line = line.map_err(|e| ExitCode::InputFile.fail(&format!("stdin: {e}"), cli.json))?;
+        let mut fields = csv::split_record(&line);
+        let result = match fields.get(column - 1) {
+            Some(field) => read_input(field.trim(), cli)
+                .map_err(|e| (ExitCode::ParseError, format!("Parse error: {e}")))
+                .and_then(|input| convert(input, direction, tz, cli)),
+            None => Err((ExitCode::ParseError, format!("no column {column} in {} fields", fields.len()))),
+        };
+        if let (Ok(c), Some(field)) = (&result, fields.get(column - 1)) {
+            log_conversion(cli, field.trim(), c.utc, c.local)?;
+        }
+        match result {
+            Ok(c) => {
+                let value = if direction == Direction::ToUtc { output.utc(c.utc) } else { output.local(c.local) };
+                if cli.append_column {
+                    fields.push(value);
+                } else {
+                    fields[column - 1] = value;
                 }
-                LocalResult::Ambiguous(_, _) => {
-                    eprintln!("Ambiguous local time in Brisbane (rare; no DST in Brisbane)");
-                    std::process::exit(5);
+                out!("{}", csv::join_record(&fields))?;
+            }
+            Err(_) if n == 1 => {
+                if cli.append_column {
+                    fields.push(if direction == Direction::ToUtc { "UTC".to_string() } else { tz.to_string() });
                 }
+                out!("{}", csv::join_record(&fields))?;
+            }
+            Err((code, msg)) => {
+                eprintln!("line {n}: {msg}");
+                out!("{line}")?;
+                status.get_or_insert(code);
             }
         }
-        "2" => {
-            let utc = Utc.from_utc_datetime(&ndt);
-            let bne = utc.with_timezone(&Brisbane);
-            println!("Brisbane: {}", bne.format("%Y-%m-%d %H:%M %Z"));
-            println!("UTC: {}", utc.to_rfc3339());
+    }
+    batch_status(status)
+}
+
+// Converts one line at a time until EOF or `quit`. The direction is asked
+// once, unless given as a flag, and again whenever the user types `dir`
+fn run_repl(cli: &Cli, input_tz: Tz, tz: Tz, output: &Output) -> Result<(), AppError> {
+    let mut direction = cli.direction;
+    loop {
+        eprint!("> ");
+        // Line by line rather than through `lines()`, which would hold the
+        // stdin lock the direction prompt needs
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) => {
+                eprintln!();
+                return Ok(());
+            }
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!();
+                return Err(ExitCode::InputFile.fail(&format!("stdin: {e}"), cli.json));
+            }
+        }
+        let line = line.trim();
+        match line {
+            "" => continue,
+            "quit" | "exit" => return Ok(()),
+            "dir" => {
+                direction = None;
+                continue;
+            }
+            _ => {}
         }
-        _ => {
-            eprintln!("Invalid choice, expected '1' or '2'");
-            std::process::exit(6);
+        let input = match read_input(line, cli) {
+            Ok(input) => input,
+            Err(e) => {
+                eprintln!("Parse error: {e}");
+                continue;
+            }
+        };
+        let chosen = match direction {
+            Some(chosen) => chosen,
+            None => match choice_or_fail(prompt_choice(input_tz, tz, cli.default_choice), cli.json)?.as_deref().map(direction_from_choice) {
+                Some(Some(chosen)) => chosen,
+                Some(None) => {
+                    eprintln!("Invalid choice, expected '1' or '2'");
+                    continue;
+                }
+                None => return Ok(()),
+            },
+        };
+        direction = Some(chosen);
+        // Choice 1 reads the input in --input-tz
+        let zone = if chosen == Direction::ToUtc { input_tz } else { tz };
+        let converted = convert(input, chosen, zone, cli);
+        if let Ok(c) = &converted {
+            log_conversion(cli, line, c.utc, c.local)?;
+        }
+        match converted {
+            Ok(c) if chosen == Direction::ToUtc => out!("UTC: {}", output.utc(c.utc))?,
+            Ok(c) => out!("{tz}: {}", output.local(c.local))?,
+            Err((_, msg)) => eprintln!("{msg}"),
+        }
+    }
+}
+
+// Converts both ends of a range read from `from`'s wall clock (or as UTC, for
+// --to-local) and shows them in `to`
+fn run_range(cli: &Cli, raw: (&str, &str), (start, end): (Input, Input), direction: Direction, from: Tz, to: Tz, output: &Output) -> Result<(), AppError> {
+    let json = cli.json;
+    let end = match (start, end) {
+        (Input::Naive(s), Input::Naive(e)) if e < s && cli.allow_wrap => Input::Naive(e + TimeDelta::days(1)),
+        _ => end,
+    };
+    let utc = |input| convert(input, direction, from, cli).map(|c| c.utc).map_err(|(code, msg)| code.fail(&msg, json));
+    let (start_utc, end_utc) = (utc(start)?, utc(end)?);
+    log_conversion(cli, raw.0, start_utc, start_utc.with_timezone(&to))?;
+    log_conversion(cli, raw.1, end_utc, end_utc.with_timezone(&to))?;
+    if end_utc < start_utc {
+        return Err(ExitCode::ParseError.fail("Range ends before it starts; use --allow-wrap to cross midnight", json));
+    }
+    let duration = end_utc - start_utc;
+    if json {
+        out!(
+            "{{\"start\": {}, \"end\": {}, \"duration_seconds\": {}}}",
+            json_result(start_utc, start_utc.with_timezone(&to), to),
+            json_result(end_utc, end_utc.with_timezone(&to), to),
+            duration.num_seconds(),
+        )?;
+        return Ok(());
+    }
+    let utc_line = format!("UTC: {} - {}", output.utc(start_utc), output.utc(end_utc));
+    let local_line = format!(
+        "{to}: {} - {}",
+        output.local(start_utc.with_timezone(&to)),
+        output.local(end_utc.with_timezone(&to)),
+    );
+    if direction == Direction::ToUtc {
+        out!("{utc_line}")?;
+        if !cli.quiet {
+            out!("{local_line}")?;
+        }
+    } else {
+        out!("{local_line}")?;
+        if !cli.quiet {
+            out!("{utc_line}")?;
+        }
+    }
+    out!("Duration: {}", span(duration))?;
+    Ok(())
+}
+
+// --recurrence: the input's wall clock in `from` (or UTC, for --to-local)
+// repeated, each occurrence shown in `to`. An occurrence lands at a different
+// time in `to` than the one before only when an offset changed in between
+fn run_recurrence(cli: &Cli, raw: &str, input: Input, direction: Direction, from: Tz, to: Tz, output: &Output) -> Result<(), AppError> {
+    let Some(recurrence) = cli.recurrence else { return Ok(()) };
+    let unit = recurrence.unit();
+    let mut results = Vec::new();
+    let mut status = None;
+    let mut gap = None;
+    for n in 1..=cli.count {
+        let days = Days::new(recurrence.days() * u64::from(n - 1));
+        let occurrence = match input {
+            Input::Naive(ndt) => ndt.checked_add_days(days).map(Input::Naive),
+            Input::Instant(dt) => dt.checked_add_days(days).map(Input::Instant),
+        };
+        let converted = occurrence
+            .ok_or_else(|| (ExitCode::ParseError, "The recurrence runs out of range".to_string()))
+            .and_then(|occurrence| convert(occurrence, direction, from, cli));
+        let c = match converted {
+            Ok(c) => c,
+            Err((code, msg)) if cli.json => return Err(code.fail(&msg, true)),
+            Err((code, msg)) => {
+                out!("{unit} {n}: {msg}")?;
+                status.get_or_insert(code);
+                continue;
+            }
+        };
+        let local = c.utc.with_timezone(&to);
+        log_conversion(cli, raw, c.utc, local)?;
+        if cli.json {
+            results.push(json_result(c.utc, local, to));
+            continue;
         }
+        // How far `to` runs ahead of the zone the wall clock is kept in
+        let anchor = if direction == Direction::ToUtc { c.local.offset().fix() } else { Utc.fix() };
+        let this_gap = TimeDelta::seconds(i64::from(local.offset().fix().local_minus_utc() - anchor.local_minus_utc()));
+        let moved = match gap.replace(this_gap) {
+            Some(before) if before != this_gap => {
+                let way = if this_gap > before { "later" } else { "earlier" };
+                format!("  <- moved {} {way}", span(this_gap - before))
+            }
+            _ => String::new(),
+        };
+        out!("{unit} {n}: {}{moved}", output.local(local))?;
+    }
+    if cli.json {
+        out!("[{}]", results.join(", "))?;
+    }
+    batch_status(status)
+}
+
+// --ambiguous-year interactive: converts both centuries a two-digit year
+// could mean, skipping a reading the calendar or the zone rules out
+fn run_centuries(cli: &Cli, raw: &str, ndt: NaiveDateTime, direction: Direction, tz: Tz, output: &Output) -> Result<(), AppError> {
+    let mut results = Vec::new();
+    for candidate in century_candidates(ndt) {
+        let converted = convert(Input::Naive(candidate), direction, tz, cli);
+        if let Ok(c) = &converted {
+            log_conversion(cli, raw, c.utc, c.local)?;
+        }
+        match converted {
+            Ok(c) if cli.json => results.push(json_result(c.utc, c.local, tz)),
+            Ok(c) => {
+                out!("As {}:", candidate.year())?;
+                out!("  UTC: {}", output.utc(c.utc))?;
+                out!("  {tz}: {}", output.local(c.local))?;
+            }
+            Err((_, msg)) => eprintln!("{}: {msg}", candidate.year()),
+        }
+    }
+    if cli.json {
+        out!("[{}]", results.join(", "))?;
+    }
+    Ok(())
+}
+
+// --ambiguous show: converts each order the date's fields could be in, day
+// first as usual, then the others, labelled with the pattern each amounts to
+fn run_readings(cli: &Cli, raw: &str, readings: Vec<(NaiveDateTime, String)>, direction: Direction, tz: Tz, output: &Output) -> Result<(), AppError> {
+    let mut results = Vec::new();
+    for (ndt, format) in readings {
+        let input = match cli.round {
+            Some(step) => round_input(Input::Naive(ndt), step).map_err(|msg| ExitCode::ParseError.fail(&msg, cli.json))?,
+            None => Input::Naive(ndt),
+        };
+        let converted = convert(input, direction, tz, cli);
+        if let Ok(c) = &converted {
+            log_conversion(cli, raw, c.utc, c.local)?;
+        }
+        match converted {
+            Ok(c) if cli.json => {
+                let result = json_result(c.utc, c.local, tz);
+                results.push(format!("{{\"format\": {}, {}", json_string(&format), &result[1..]));
+            }
+            Ok(c) => {
+                out!("As {} ({format}):", input.naive().date())?;
+                out!("  UTC: {}", output.utc(c.utc))?;
+                out!("  {tz}: {}", output.local(c.local))?;
+            }
+            Err((_, msg)) => eprintln!("{format}: {msg}"),
+        }
+    }
+    if cli.json {
+        out!("[{}]", results.join(", "))?;
+    }
+    Ok(())
+}
+
+fn main() -> std::process::ExitCode {
+    match run() {
+        Ok(()) => std::process::ExitCode::SUCCESS,
+        Err(e) => e.report(),
+    }
+}
+
+fn run() -> Result<(), AppError> {
+    let mut cli = Cli::parse(env::args().skip(1)).map_err(|e| match e.code {
+        ExitCode::Usage => AppError::Usage(Some(e.message)),
+        code => code.fail(&e.message, false),
+    })?;
+    let json = cli.json;
+    NO_NEWLINE.store(cli.no_newline, Ordering::Relaxed);
+    if cli.help {
+        return emit_text(HELP);
+    }
+
+    if cli.list_formats {
+        return emit_text(&format_list());
+    }
+
+    if cli.list_timezones {
+        return list_timezones(cli.list_filter.as_deref());
+    }
+
+    // Fail on an unwritable log before anything is converted
+    if let Some(path) = &cli.log_file {
+        open_log(path, json)?;
+    }
+
+    let config = Config::load().map_err(|e| ExitCode::Usage.fail(&e, json))?;
+    if cli.direction.is_none() {
+        cli.direction = config.direction;
+    }
+    // UTC_TIME_DEFAULT_CHOICE beats the config file's default_choice
+    cli.default_choice = match env::var("UTC_TIME_DEFAULT_CHOICE") {
+        Ok(choice) if !choice.trim().is_empty() => match direction_from_choice(choice.trim()) {
+            Some(direction) => Some(direction),
+            None => return Err(ExitCode::Usage.fail(&format!("UTC_TIME_DEFAULT_CHOICE must be 1 or 2, got '{choice}'"), json)),
+        },
+        _ => config.default_choice,
+    };
+
+    // --tz or --tz-file beats UTC_TIME_TZ, then the config file, then the
+    // historical Brisbane default. The first zone reads the input; any others
+    // are extra output lines
+    if cli.tz.is_some() && cli.tz_file.is_some() {
+        return Err(AppError::Usage(Some("--tz and --tz-file both name the zones; pick one".to_string())));
+    }
+    let zones = match (&cli.tz, &cli.tz_file) {
+        (Some(list), _) => parse_zones(list, json)?,
+        (None, Some(path)) => read_tz_file(path, json)?,
+        (None, None) => match env::var("UTC_TIME_TZ") {
+            Ok(list) if !list.is_empty() => parse_zones(&list, json)?,
+            _ => match &config.tz {
+                Some(list) => parse_zones(list, json)?,
+                None => vec![Brisbane],
+            },
+        },
+    };
+    let tz = zones[0];
+    match cli.bare_tz {
+        Some(BareTz::Utc) => cli.parse_opts.today_zone = Tz::UTC,
+        Some(BareTz::Local) => cli.parse_opts.today_zone = tz,
+        None => {}
+    }
+    let from = cli.from.as_deref().map(|name| parse_tz(name, json)).transpose()?;
+    let to = cli.to.as_deref().map(|name| parse_tz(name, json)).transpose()?;
+    let input_tz = cli.input_tz.as_deref().map(|name| parse_tz(name, json)).transpose()?;
+    let color = match cli.color {
+        Color::Always => true,
+        Color::Never => false,
+        Color::Auto => io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none(),
+    };
+    let format = cli.format.clone().or(config.format).unwrap_or(Format::Rfc3339);
+    let output = Output::new(format, display_format(cli.clock12, !cli.no_offset, cli.lower_ampm), color, cli.precision)
+        .with_locale(cli.locale)
+        .with_utc_z(cli.utc_z)
+        .with_format_list(cli.formats.clone())
+        .with_epoch_reference(cli.parse_opts.epoch_reference);
+
+    // Bypasses parsing and the menu; only the output knobs apply
+    if cli.utc_now {
+        out!("{}", output.utc(Utc::now()))?;
+        return Ok(());
+    }
+
+    // Offsets change with DST, so the answer is only for now
+    if let Some((a, b)) = &cli.diff_zones {
+        if cli.input.is_some() {
+            return Err(ExitCode::TooManyArgs.fail("--diff-zones compares the zones now; drop the positional arguments", false));
+        }
+        let (a, b, now) = (parse_tz(a, json)?, parse_tz(b, json)?, Utc::now());
+        if json {
+            out!(
+                "{{\"a\": {}, \"b\": {}, \"difference_seconds\": {}}}",
+                json_string(a.name()),
+                json_string(b.name()),
+                zone_gap_seconds(a, b, now),
+            )?;
+        } else {
+            out!("{}", zone_gap_line(a, b, now))?;
+        }
+        return Ok(());
+    }
+
+    // Each zone gets a heading when there are several
+    if let Some(year) = cli.transitions {
+        for zone in &zones {
+            if zones.len() > 1 {
+                out!("{zone}:")?;
+            }
+            let changes = transitions_in_year(*zone, year);
+            if changes.is_empty() {
+                out!("No offset changes in {year}")?;
+            }
+            for (utc, offset) in changes {
+                out!("{}", transition_line(*zone, utc.and_utc(), offset))?;
+            }
+        }
+        return Ok(());
+    }
+
+    if cli.world {
+        let zones = if cli.tz.is_some() || cli.tz_file.is_some() { zones.as_slice() } else { &WORLD_ZONES[..] };
+        return world_clock(zones, &output, json);
+    }
+
+    if cli.repl {
+        return run_repl(&cli, input_tz.unwrap_or(tz), tz, &output);
+    }
+
+    if cli.csv {
+        let Some(column) = cli.column else {
+            return Err(AppError::Usage(Some("--csv needs --column N to say which field to convert".to_string())));
+        };
+        if cli.input.is_some() {
+            return Err(ExitCode::TooManyArgs.fail("--csv reads records from stdin; drop the positional arguments", false));
+        }
+        let direction = cli.direction.unwrap_or(Direction::ToUtc);
+        let tz = reading_zone(input_tz, tz, direction);
+        return run_csv(&cli, io::stdin().lock(), column, direction, tz, &output);
+    }
+    if cli.column.is_some() || cli.append_column {
+        return Err(AppError::Usage(Some("--column and --append-column only apply with --csv".to_string())));
+    }
+    if cli.group_by_date && !cli.batch && cli.input_file.is_none() {
+        return Err(AppError::Usage(Some("--group-by-date only applies with --stdin or --input-file".to_string())));
+    }
+    if cli.echo_input && !cli.batch && cli.input_file.is_none() {
+        return Err(AppError::Usage(Some("--echo-input only applies with --stdin or --input-file".to_string())));
+    }
+
+    if cli.batch || cli.input_file.is_some() {
+        if cli.batch && cli.input_file.is_some() {
+            return Err(AppError::Usage(Some("--stdin and --input-file both name the input; pick one".to_string())));
+        }
+        if cli.input.is_some() {
+            return Err(ExitCode::TooManyArgs.fail("Input is read line by line; drop the positional arguments", false));
+        }
+        // The lines come from elsewhere, so there is nobody to prompt
+        let direction = cli.direction.unwrap_or(Direction::ToUtc);
+        let tz = reading_zone(input_tz, tz, direction);
+        return match cli.input_file.as_deref() {
+            Some(path) => match File::open(path) {
+                Ok(file) => run_batch(&cli, BufReader::new(file), Some(path), direction, tz, &output),
+                Err(e) => Err(ExitCode::InputFile.fail(&format!("Cannot read {path}: {e}"), json)),
+            },
+            None => run_batch(&cli, io::stdin().lock(), None, direction, tz, &output),
+        };
+    }
+
+    let read = |s: &str| read_input(s, &cli).map_err(|e| ExitCode::ParseError.fail(&format!("Parse error: {e}"), json));
+
+    // Both sides are read like a --to-utc input unless --to-local says they are UTC
+    if let Some((a, b)) = &cli.compare {
+        if cli.input.is_some() {
+            return Err(ExitCode::TooManyArgs.fail("--compare takes both inputs itself; drop the positional arguments", false));
+        }
+        let direction = cli.direction.unwrap_or(Direction::ToUtc);
+        let utc = |s: &str| convert(read(s)?, direction, tz, &cli).map(|c| c.utc).map_err(|(code, msg)| code.fail(&msg, json));
+        let (a_utc, b_utc) = (utc(a)?, utc(b)?);
+        let earlier = match a_utc.cmp(&b_utc) {
+            std::cmp::Ordering::Less => Some("A"),
+            std::cmp::Ordering::Greater => Some("B"),
+            std::cmp::Ordering::Equal => None,
+        };
+        let gap = (b_utc - a_utc).abs();
+        if json {
+            out!(
+                "{{\"a\": {}, \"b\": {}, \"earlier\": {}, \"difference_seconds\": {}}}",
+                json_string(&a_utc.to_rfc3339()),
+                json_string(&b_utc.to_rfc3339()),
+                earlier.map_or("null".to_string(), |side| json_string(&side.to_lowercase())),
+                gap.num_seconds(),
+            )?;
+            return Ok(());
+        }
+        out!("A: {}", output.utc(a_utc))?;
+        out!("B: {}", output.utc(b_utc))?;
+        match earlier {
+            Some(side) => out!("{side} is earlier by {}", span(gap))?,
+            None => out!("A and B are the same instant")?,
+        }
+        return Ok(());
+    }
+
+    // With no argument a piped line stands in for it, as in `echo TIME | utc_time`
+    let piped;
+    let raw = match cli.input.as_deref() {
+        Some(_) if cli.from_clipboard => {
+            return Err(ExitCode::TooManyArgs.fail("--from-clipboard reads the input itself; drop the positional arguments", false));
+        }
+        None if cli.from_clipboard => {
+            piped = read_clipboard(json)?;
+            piped.as_str()
+        }
+        Some(raw) => raw,
+        None if !io::stdin().is_terminal() => {
+            let mut line = String::new();
+            io::stdin().read_line(&mut line).map_err(|e| ExitCode::InputFile.fail(&format!("stdin: {e}"), json))?;
+            piped = line.trim().to_string();
+            if piped.is_empty() {
+                return Err(AppError::Usage(None));
+            }
+            piped.as_str()
+        }
+        None => return Err(AppError::Usage(None)),
+    };
+    // An empty clipboard is reported by the parser, like any other bad paste
+    if raw.trim().is_empty() && !cli.from_clipboard {
+        return Err(AppError::Usage(Some("No time provided".to_string())));
+    }
+    // For a range, `input` is its start
+    let (input, range_end, range_text) = match split_range(raw) {
+        Some((start, end)) => (read(&start)?, Some(read(&end)?), Some((start, end))),
+        None => (read(raw)?, None, None),
+    };
+
+    if cli.validate {
+        for input in std::iter::once(input).chain(range_end) {
+            match input {
+                Input::Naive(ndt) => out!("{ndt}")?,
+                Input::Instant(dt) => out!("{}", dt.to_rfc3339())?,
+            }
+        }
+        return Ok(());
+    }
+
+    // The input is the zone's wall clock unless --to-local says it is UTC;
+    // either way the offset is whatever applied at that instant
+    if cli.offset_only {
+        let direction = cli.direction.unwrap_or(Direction::ToUtc);
+        let c = convert(input, direction, tz, &cli).map_err(|(code, msg)| code.fail(&msg, json))?;
+        out!("{}", c.local.offset().fix())?;
+        return Ok(());
+    }
+
+    if let Some(list) = &cli.chain {
+        if from.is_some() || to.is_some() || range_end.is_some() {
+            let msg = "--chain takes one time and names its own zones; drop --from, --to and ranges";
+            return Err(AppError::Usage(Some(msg.to_string())));
+        }
+        return run_chain(&cli, raw, input, &parse_chain(list, json)?, &output);
+    }
+
+    // Zone-to-zone conversion skips the menu; a missing side falls back to --tz
+    if from.is_some() || to.is_some() {
+        let (from, to) = (from.unwrap_or(tz), to.unwrap_or(tz));
+        if let (Some(end), Some((a, b))) = (range_end, &range_text) {
+            return run_range(&cli, (a, b), (input, end), Direction::ToUtc, from, to, &output);
+        }
+        if cli.recurrence.is_some() {
+            return run_recurrence(&cli, raw, input, Direction::ToUtc, from, to, &output);
+        }
+        fail_ambiguous_json(&cli, input, Direction::ToUtc, from)?;
+        let Conversion { utc, local: from_dt, .. } =
+            convert(input, Direction::ToUtc, from, &cli).map_err(|(code, msg)| code.fail(&msg, json))?;
+        let to_dt = utc.with_timezone(&to);
+        log_conversion(&cli, raw, utc, to_dt)?;
+        if json {
+            out!("{}", json_result(utc, to_dt, to))?;
+            return Ok(());
+        }
+        if !cli.quiet {
+            out!("{from}: {}", output.local(from_dt))?;
+        }
+        out!("{to}: {}", output.local(to_dt))?;
+        if !cli.quiet {
+            out!("{}", offset_line(to_dt))?;
+        }
+        if cli.calendar_info {
+            out!("{}", calendar_line(to_dt))?;
+        }
+        if let Some(window) = cli.business_hours {
+            out!("{}", business_hours_line(to_dt, window))?;
+        }
+        if cli.bizdays_until {
+            out!("{}", bizdays_line(Utc::now().with_timezone(&to).date_naive(), to_dt.date_naive()))?;
+        }
+        if cli.dst_status {
+            out!("{}", dst_status_line(to_dt))?;
+        }
+        if cli.since_midnight {
+            out!("{}", since_midnight_line(to_dt))?;
+        }
+        if cli.delta {
+            out!("Delta: {}", humanize(utc - Utc::now()))?;
+        }
+        if let Some(reference) = &cli.relative_to {
+            out!("{}", relative_line(&cli, reference, utc, Direction::ToUtc, from)?)?;
+        }
+        return Ok(());
+    }
+
+    // Only prompt when someone can answer; piped runs default to local -> UTC
+    let direction = match cli.direction {
+        Some(direction) => direction,
+        // An instant needs no reading in the zone; show it as local -> UTC
+        None if matches!(input, Input::Instant(_)) => Direction::ToUtc,
+        None if io::stdin().is_terminal() => {
+            let Some(answer) = choice_or_fail(prompt_choice(input_tz.unwrap_or(tz), tz, cli.default_choice), json)? else {
+                eprintln!();
+                return Err(ExitCode::NoChoice.fail("no choice provided (stdin closed)", json));
+            };
+            direction_from_choice(&answer).ok_or_else(|| ExitCode::BadChoice.fail("Invalid choice, expected '1' or '2'", json))?
+        }
+        None => Direction::ToUtc,
+    };
+    // Choice 1 reads the input, and reports it, in --input-tz
+    let (tz, zones) = match input_tz {
+        Some(zone) if direction == Direction::ToUtc => (zone, [&[zone], &zones[1..]].concat()),
+        _ => (tz, zones),
+    };
+
+    if let (Some(end), Some((a, b))) = (range_end, &range_text) {
+        return run_range(&cli, (a, b), (input, end), direction, tz, tz, &output);
+    }
+    if cli.recurrence.is_some() {
+        let to = if direction == Direction::ToUtc { Tz::UTC } else { tz };
+        return run_recurrence(&cli, raw, input, direction, tz, to, &output);
+    }
+
+    // Only a year that really came from two digits has a second reading
+    if cli.both_centuries
+        && let Input::Naive(ndt) = input
+        && parse_traced(raw, &cli.parse_opts).is_ok_and(|(_, trace)| trace.year_rewrite.is_some())
+    {
+        return run_centuries(&cli, raw, ndt, direction, tz, &output);
+    }
+    if cli.all_readings {
+        let readings = parse_all(raw, &cli.parse_opts);
+        if readings.len() > 1 {
+            return run_readings(&cli, raw, readings, direction, tz, &output);
+        }
+    }
+
+    fail_ambiguous_json(&cli, input, direction, tz)?;
+    let Conversion { utc, local: local_dt, .. } =
+        convert(input, direction, tz, &cli).map_err(|(code, msg)| code.fail(&msg, json))?;
+    log_conversion(&cli, raw, utc, local_dt)?;
+
+    // Like --world, the preset zones stand in unless --tz or --tz-file is given
+    if cli.offset_table {
+        let mut zones = if cli.tz.is_some() || cli.tz_file.is_some() { zones } else { WORLD_ZONES.to_vec() };
+        if json {
+            zones.sort_by_key(|z| utc.with_timezone(z).offset().fix().local_minus_utc());
+            let results: Vec<String> = zones.iter().map(|z| json_result(utc, utc.with_timezone(z), *z)).collect();
+            out!("[{}]", results.join(", "))?;
+        } else {
+            for row in &offset_table(utc, &zones) {
+                out!("{row}")?;
+            }
+        }
+        return Ok(());
+    }
+
+    if json {
+        if zones.len() == 1 {
+            out!("{}", json_result(utc, local_dt, tz))?;
+        } else {
+            let results: Vec<String> = zones.iter().map(|z| json_result(utc, utc.with_timezone(z), *z)).collect();
+            out!("[{}]", results.join(", "))?;
+        }
+        return Ok(());
+    }
+
+    let utc_lines = output.utc_lines(utc);
+    let local_lines: Vec<String> = zones
+        .iter()
+        .map(|z| format!("{z}: {}", output.local(utc.with_timezone(z))))
+        .collect();
+    // --quiet keeps only the target of the conversion
+    if direction == Direction::ToUtc {
+        for line in &utc_lines {
+            out!("{line}")?;
+        }
+        if !cli.quiet {
+            for line in &local_lines {
+                out!("{line}")?;
+            }
+        }
+    } else {
+        for line in &local_lines {
+            out!("{line}")?;
+        }
+        if !cli.quiet {
+            for line in &utc_lines {
+                out!("{line}")?;
+            }
+        }
+    }
+    // Each zone line already names its zone; the offset summary is for one
+    if !cli.quiet && zones.len() == 1 {
+        out!("{}", offset_line(local_dt))?;
+    }
+    if cli.calendar_info {
+        out!("{}", calendar_line(local_dt))?;
+    }
+    if let Some(window) = cli.business_hours {
+        out!("{}", business_hours_line(local_dt, window))?;
+    }
+    if cli.bizdays_until {
+        out!("{}", bizdays_line(Utc::now().with_timezone(&tz).date_naive(), local_dt.date_naive()))?;
+    }
+    if cli.dst_status {
+        out!("{}", dst_status_line(local_dt))?;
+    }
+    // Counted in the zone the conversion lands in
+    if cli.since_midnight {
+        let target = if direction == Direction::ToUtc { utc.with_timezone(&Tz::UTC) } else { local_dt };
+        out!("{}", since_midnight_line(target))?;
+    }
+    if cli.delta {
+        out!("Delta: {}", humanize(utc - Utc::now()))?;
+    }
+    if let Some(reference) = &cli.relative_to {
+        out!("{}", relative_line(&cli, reference, utc, direction, tz)?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_error_maps_to_its_exit_code() {
+        assert_eq!(ExitCode::UnknownTimezone.fail("no such zone", true).exit_code(), 7);
+        assert_eq!(AppError::Usage(None).exit_code(), 1);
+        assert_eq!(AppError::Reported(ExitCode::Ambiguous).exit_code(), ExitCode::Ambiguous as u8);
+        assert_eq!(AppError::ClosedPipe.exit_code(), 0);
+        assert_eq!(ExitCode::Stdout.fail("cannot write to stdout", false).exit_code(), 11);
     }
 }
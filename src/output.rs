@@ -0,0 +1,704 @@
+//! Rendering of conversion results, in text and JSON.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike, Days, FixedOffset, Locale, NaiveDate, NaiveTime, Offset, SecondsFormat, TimeDelta, TimeZone, Utc, Weekday};
+use chrono_tz::{OffsetComponents, Tz};
+use utc_time::EpochReference;
+
+// Human-readable layouts for the local side of a conversion. The numeric
+// offset is spelled out since `%Z` is only a number for many zones, and
+// abbreviations alone hide half-hour offsets such as Adelaide's
+pub const DISPLAY_FMT: &str = "%Y-%m-%d %H:%M:%S %Z (%:z)";
+pub const DISPLAY_FMT_12H: &str = "%Y-%m-%d %I:%M:%S %p %Z (%:z)";
+
+// The terser layouts for --no-offset
+const DISPLAY_FMT_BARE: &str = "%Y-%m-%d %H:%M:%S %Z";
+const DISPLAY_FMT_12H_BARE: &str = "%Y-%m-%d %I:%M:%S %p %Z";
+
+// --am-pm-lowercase spells the meridiem `pm` rather than `PM`
+const DISPLAY_FMT_12H_LOWER: &str = "%Y-%m-%d %I:%M:%S %P %Z (%:z)";
+const DISPLAY_FMT_12H_LOWER_BARE: &str = "%Y-%m-%d %I:%M:%S %P %Z";
+
+// Picks the human-readable layout for --12h, --no-offset and
+// --am-pm-lowercase; the last only matters with --12h
+pub fn display_format(clock12: bool, offset: bool, lower_ampm: bool) -> &'static str {
+    match (clock12, offset, lower_ampm) {
+        (false, true, _) => DISPLAY_FMT,
+        (true, true, false) => DISPLAY_FMT_12H,
+        (true, true, true) => DISPLAY_FMT_12H_LOWER,
+        (false, false, _) => DISPLAY_FMT_BARE,
+        (true, false, false) => DISPLAY_FMT_12H_BARE,
+        (true, false, true) => DISPLAY_FMT_12H_LOWER_BARE,
+    }
+}
+
+// What `date` prints with no arguments; `%e` pads the day with a space
+const DATE_FMT: &str = "%a %b %e %H:%M:%S %Z %Y";
+
+// iCalendar's basic-format UTC stamp (RFC 5545 DATE-TIME), e.g. "20250922T110000Z"
+const ICAL_FMT: &str = "%Y%m%dT%H%M%SZ";
+
+// How the result lines are rendered; only some formats change the local side
+#[derive(Clone, Debug, PartialEq)]
+pub enum Format {
+    Rfc3339,
+    Rfc2822,
+    Epoch,
+    // Milliseconds since the epoch, as JavaScript counts
+    EpochMs,
+    // The default `date` layout, e.g. "Mon Sep 22 21:00:00 AEST 2025"
+    Date,
+    // The UTC stamp calendar files use for DTSTART and friends
+    Ical,
+    // Validated strftime pattern from --out-format
+    Custom(String),
+}
+
+// Every --format keyword, in the order --list-formats shows them
+const FORMAT_NAMES: [&str; 6] = ["rfc3339", "rfc2822", "epoch", "epoch-ms", "date", "ical"];
+
+// --list-formats: each keyword beside what it prints for one fixed instant
+pub fn format_list() -> String {
+    let utc = DateTime::from_timestamp(1_758_538_800, 0).expect("in range");
+    let mut list: String = FORMAT_NAMES
+        .iter()
+        .map(|name| format!("{name:<10}{}\n", Format::parse(name).expect("listed names parse").render(utc, None)))
+        .collect();
+    list.push_str("--out-format takes a strftime pattern instead, e.g. '%d/%m/%Y %H:%M'\n");
+    list
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Result<Format, String> {
+        match name {
+            "rfc3339" => Ok(Format::Rfc3339),
+            "rfc2822" => Ok(Format::Rfc2822),
+            "epoch" => Ok(Format::Epoch),
+            "epoch-ms" => Ok(Format::EpochMs),
+            "date" => Ok(Format::Date),
+            "ical" => Ok(Format::Ical),
+            _ => Err(format!("Unknown format '{name}'; expected rfc3339, rfc2822, epoch, epoch-ms, date or ical")),
+        }
+    }
+
+    // `--format rfc3339,epoch`: every name in order. One name is a list of one
+    pub fn parse_list(list: &str) -> Result<Vec<Format>, String> {
+        list.split(',').map(|name| Format::parse(name.trim())).collect()
+    }
+
+    // The keyword a list line is labelled with; a pattern labels itself
+    fn name(&self) -> &str {
+        match self {
+            Format::Rfc3339 => "rfc3339",
+            Format::Rfc2822 => "rfc2822",
+            Format::Epoch => "epoch",
+            Format::EpochMs => "epoch-ms",
+            Format::Date => "date",
+            Format::Ical => "ical",
+            Format::Custom(pattern) => pattern,
+        }
+    }
+
+    // Rejects unknown specifiers up front, since formatting them would panic later
+    pub fn custom(pattern: &str) -> Result<Format, String> {
+        if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+            return Err(format!("Invalid --out-format pattern '{pattern}'; see chrono's strftime specifiers"));
+        }
+        Ok(Format::Custom(pattern.to_string()))
+    }
+
+    // RFC2822 and the numeric forms are machine formats, so only strftime
+    // layouts follow `locale`
+    fn render(&self, utc: DateTime<Utc>, locale: Option<Locale>) -> String {
+        match self {
+            Format::Rfc3339 => utc.to_rfc3339(),
+            Format::Rfc2822 => utc.to_rfc2822(),
+            Format::Epoch => utc.timestamp().to_string(),
+            Format::EpochMs => utc.timestamp_millis().to_string(),
+            Format::Date => strftime(utc, DATE_FMT, locale),
+            Format::Ical => utc.format(ICAL_FMT).to_string(),
+            Format::Custom(pattern) => strftime(utc, pattern, locale),
+        }
+    }
+
+    // `display` is the human-readable layout used when the format has no local form
+    fn render_local(&self, local_dt: DateTime<Tz>, display: &str, locale: Option<Locale>) -> String {
+        match self {
+            Format::Rfc2822 => local_dt.to_rfc2822(),
+            Format::Date => strftime(local_dt, DATE_FMT, locale),
+            Format::Custom(pattern) => strftime(local_dt, pattern, locale),
+            Format::Rfc3339 | Format::Epoch | Format::EpochMs | Format::Ical => strftime(local_dt, display, locale),
+        }
+    }
+}
+
+// Month and weekday names, and AM/PM, come from `locale` when there is one
+fn strftime<Z: TimeZone>(dt: DateTime<Z>, pattern: &str, locale: Option<Locale>) -> String
+where
+    Z::Offset: std::fmt::Display,
+{
+    match locale {
+        Some(locale) => dt.format_localized(pattern, locale).to_string(),
+        None => dt.format(pattern).to_string(),
+    }
+}
+
+// ANSI SGR colours for the two sides of a conversion
+const UTC_COLOR: &str = "36";
+const LOCAL_COLOR: &str = "32";
+
+// The chosen format plus the human-readable layout it falls back to
+pub struct Output {
+    format: Format,
+    display: &'static str,
+    color: bool,
+    // Fractional digits for RFC3339 and the human-readable seconds; `None`
+    // keeps chrono's default there and whole seconds here
+    precision: Option<u8>,
+    // From --locale; `None` is chrono's English
+    locale: Option<Locale>,
+    // --utc-designator z: RFC3339 UTC ends in `Z` rather than `+00:00`
+    use_z: bool,
+    // A --format list, when it names more than one format
+    list: Vec<Format>,
+    // --epoch-reference: where the epoch formats count from
+    epoch: EpochReference,
+}
+
+impl Output {
+    pub fn new(format: Format, display: &'static str, color: bool, precision: Option<u8>) -> Output {
+        Output { format, display, color, precision, locale: None, use_z: false, list: Vec::new(), epoch: EpochReference::Unix }
+    }
+
+    pub fn with_locale(self, locale: Option<Locale>) -> Output {
+        Output { locale, ..self }
+    }
+
+    pub fn with_utc_z(self, use_z: bool) -> Output {
+        Output { use_z, ..self }
+    }
+
+    pub fn with_format_list(self, list: Vec<Format>) -> Output {
+        Output { list, ..self }
+    }
+
+    pub fn with_epoch_reference(self, epoch: EpochReference) -> Output {
+        Output { epoch, ..self }
+    }
+
+    pub fn utc(&self, utc: DateTime<Utc>) -> String {
+        self.utc_as(&self.format, utc)
+    }
+
+    // The UTC line, or with a format list one line per format, labelled and
+    // in the order given
+    pub fn utc_lines(&self, utc: DateTime<Utc>) -> Vec<String> {
+        if self.list.len() < 2 {
+            return vec![format!("UTC: {}", self.utc(utc))];
+        }
+        self.list.iter().map(|format| format!("UTC ({}): {}", format.name(), self.utc_as(format, utc))).collect()
+    }
+
+    fn utc_as(&self, format: &Format, utc: DateTime<Utc>) -> String {
+        let text = match format {
+            Format::Rfc3339 => rfc3339(utc, self.precision, self.use_z),
+            Format::Epoch => self.epoch.count(utc).to_string(),
+            Format::EpochMs => (utc.timestamp_millis() - self.epoch.unix_offset() * 1000).to_string(),
+            _ => format.render(utc, self.locale),
+        };
+        self.paint(UTC_COLOR, text)
+    }
+
+    pub fn local(&self, local_dt: DateTime<Tz>) -> String {
+        let display = match self.precision {
+            Some(digits @ 1..) => with_fraction(self.display, local_dt.timestamp_subsec_nanos(), digits),
+            _ => self.display.to_string(),
+        };
+        self.paint(LOCAL_COLOR, self.format.render_local(local_dt, &display, self.locale))
+    }
+
+    fn paint(&self, code: &str, text: String) -> String {
+        if self.color { format!("\x1b[{code}m{text}\x1b[0m") } else { text }
+    }
+}
+
+// `display` with its seconds followed by `digits` fractional digits,
+// truncated like the RFC3339 line. chrono's `%.Nf` only takes 3, 6 or 9, so
+// the digits go in as literal text; a leap second's nanos run past a billion
+fn with_fraction(display: &str, nanos: u32, digits: u8) -> String {
+    let fraction = format!("{:09}", nanos % 1_000_000_000);
+    display.replacen("%S", &format!("%S.{}", &fraction[..usize::from(digits)]), 1)
+}
+
+// RFC3339 with exactly `digits` fractional digits, truncating, or chrono's
+// default without. chrono only offers 0, 3, 6 and 9, so the rest are cut
+// from the nanosecond form. `use_z` writes UTC as `Z`
+fn rfc3339(utc: DateTime<Utc>, digits: Option<u8>, use_z: bool) -> String {
+    match digits {
+        None => utc.to_rfc3339_opts(SecondsFormat::AutoSi, use_z),
+        Some(0) => utc.to_rfc3339_opts(SecondsFormat::Secs, use_z),
+        Some(3) => utc.to_rfc3339_opts(SecondsFormat::Millis, use_z),
+        Some(6) => utc.to_rfc3339_opts(SecondsFormat::Micros, use_z),
+        Some(9) => utc.to_rfc3339_opts(SecondsFormat::Nanos, use_z),
+        Some(digits) => {
+            let full = utc.to_rfc3339_opts(SecondsFormat::Nanos, use_z);
+            let dot = full.rfind('.').expect("nanosecond form has a fraction");
+            let keep = dot + 1 + usize::from(digits);
+            format!("{}{}", &full[..keep], &full[dot + 10..])
+        }
+    }
+}
+
+// Quotes `s` as a JSON string literal
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub fn json_result(utc: DateTime<Utc>, local_dt: DateTime<Tz>, tz: Tz) -> String {
+    format!(
+        "{{\"utc\": {}, \"local\": {}, \"tz\": {}, \"epoch\": {}}}",
+        json_string(&utc.to_rfc3339()),
+        json_string(&local_dt.format(DISPLAY_FMT).to_string()),
+        json_string(tz.name()),
+        utc.timestamp(),
+    )
+}
+
+// One --transitions entry, e.g.
+// "2025-10-04 16:00:00 UTC -> +11:00 (AEDT, 2025-10-05 03:00:00 local)"
+pub fn transition_line(tz: Tz, utc: DateTime<Utc>, offset: FixedOffset) -> String {
+    let local = utc.with_timezone(&tz);
+    format!(
+        "{} UTC -> {offset} ({}, {} local)",
+        utc.format("%Y-%m-%d %H:%M:%S"),
+        local.format("%Z"),
+        local.format("%Y-%m-%d %H:%M:%S"),
+    )
+}
+
+// --offset-table: one row per zone for the instant, furthest behind UTC
+// first, e.g. "+09:00  Asia/Tokyo  Mon 2025-09-22 20:00 JST". Zones sharing an
+// offset keep the order they were given in
+pub fn offset_table(utc: DateTime<Utc>, zones: &[Tz]) -> Vec<String> {
+    let mut rows: Vec<DateTime<Tz>> = zones.iter().map(|z| utc.with_timezone(z)).collect();
+    rows.sort_by_key(|local| local.offset().fix().local_minus_utc());
+    let width = zones.iter().map(|z| z.name().len()).max().unwrap_or(0);
+    rows.iter()
+        .map(|local| {
+            let name = local.timezone().name();
+            format!("{}  {name:<width$}  {}", local.offset().fix(), local.format("%a %Y-%m-%d %H:%M %Z"))
+        })
+        .collect()
+}
+
+// e.g. "DST status: daylight (AEDT)", for --dst-status
+pub fn dst_status_line(local_dt: DateTime<Tz>) -> String {
+    let phase = if local_dt.offset().dst_offset().is_zero() { "standard" } else { "daylight" };
+    format!("DST status: {phase} ({})", local_dt.format("%Z"))
+}
+
+// --json for a local time a DST change repeats: both readings, earliest first,
+// so the caller can pick one
+pub fn json_ambiguous(earliest: DateTime<Tz>, latest: DateTime<Tz>) -> String {
+    let [a, b] = [earliest, latest].map(|dt| json_string(&dt.with_timezone(&Utc).to_rfc3339()));
+    format!(
+        "{{\"local\": {}, \"tz\": {}, \"ambiguous\": [{a}, {b}]}}",
+        json_string(&earliest.naive_local().to_string()),
+        json_string(earliest.timezone().name()),
+    )
+}
+
+// One side of an ambiguous local time, as shown in the error message
+pub fn candidate(dt: DateTime<Tz>) -> String {
+    format!("{} ({})", dt.format(DISPLAY_FMT), dt.with_timezone(&Utc).to_rfc3339())
+}
+
+// e.g. "Offset: +10:00 (AEST, DST: no)"
+pub fn offset_line(local_dt: DateTime<Tz>) -> String {
+    let offset = local_dt.offset();
+    let dst = if offset.dst_offset().is_zero() { "no" } else { "yes" };
+    format!("Offset: {} ({}, DST: {dst})", offset.fix(), local_dt.format("%Z"))
+}
+
+// e.g. "Europe/London is 9 hours behind Australia/Brisbane", at `utc`.
+// Half- and quarter-hour zones give fractions such as 9.5 or 5.75
+pub fn zone_gap_line(a: Tz, b: Tz, utc: DateTime<Utc>) -> String {
+    let gap = zone_gap_seconds(a, b, utc);
+    if gap == 0 {
+        return format!("{a} is at the same time as {b}");
+    }
+    let hours = format!("{:.2}", f64::from(gap.abs()) / 3600.0);
+    let hours = hours.trim_end_matches('0').trim_end_matches('.');
+    let unit = if hours == "1" { "hour" } else { "hours" };
+    let side = if gap > 0 { "ahead of" } else { "behind" };
+    format!("{a} is {hours} {unit} {side} {b}")
+}
+
+// How far `a`'s clock is ahead of `b`'s at `utc`, negative when behind
+pub fn zone_gap_seconds(a: Tz, b: Tz, utc: DateTime<Utc>) -> i32 {
+    let offset = |z: Tz| utc.with_timezone(&z).offset().fix().local_minus_utc();
+    offset(a) - offset(b)
+}
+
+// e.g. "Calendar: day 265 of 2025, ISO week 2025-W39"
+pub fn calendar_line(local_dt: DateTime<Tz>) -> String {
+    let week = local_dt.iso_week();
+    format!(
+        "Calendar: day {} of {}, ISO week {}-W{:02}",
+        local_dt.ordinal(),
+        local_dt.year(),
+        week.year(),
+        week.week(),
+    )
+}
+
+// e.g. "Within business hours: no (Saturday)". The window's end is
+// exclusive, and an end before the start wraps past midnight
+pub fn business_hours_line(local_dt: DateTime<Tz>, (start, end): (NaiveTime, NaiveTime)) -> String {
+    let t = local_dt.time();
+    let in_window = if start <= end { start <= t && t < end } else { t >= start || t < end };
+    let weekday = local_dt.weekday();
+    let answer = match (in_window, weekday) {
+        (_, Weekday::Sat | Weekday::Sun) => format!("no ({})", day_name(weekday)),
+        (true, _) => "yes".to_string(),
+        (false, _) => format!("no (outside {}-{})", start.format("%H:%M"), end.format("%H:%M")),
+    };
+    format!("Within business hours: {answer}")
+}
+
+// e.g. "Business days until: 3", for --bizdays-until
+pub fn bizdays_line(today: NaiveDate, date: NaiveDate) -> String {
+    format!("Business days until: {}", business_days(today, date))
+}
+
+// The weekdays after `from` up to and including `to`; negative, counting
+// back, when `to` is earlier
+fn business_days(from: NaiveDate, to: NaiveDate) -> i64 {
+    if to < from {
+        return -business_days(to, from);
+    }
+    let days = (to - from).num_days();
+    // Every whole week has five; only the last part-week needs looking at
+    let rest = from + Days::new((days / 7 * 7).unsigned_abs());
+    let weekdays = rest.iter_days().skip(1).take_while(|d| *d <= to);
+    days / 7 * 5 + weekdays.filter(|d| !matches!(d.weekday(), Weekday::Sat | Weekday::Sun)).count() as i64
+}
+
+fn day_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+// The first instant of `dt`'s local day. Where a DST change skips midnight the
+// day starts at the first wall-clock minute that exists
+fn local_midnight(dt: DateTime<Tz>) -> DateTime<Tz> {
+    let midnight = dt.date_naive().and_time(NaiveTime::MIN);
+    (0..24 * 60)
+        .find_map(|m| dt.timezone().from_local_datetime(&(midnight + TimeDelta::minutes(m))).earliest())
+        .expect("every day has a valid local time")
+}
+
+// e.g. "Since midnight: 75600s (Australia/Brisbane)", counted in real seconds
+// so DST days are not assumed to be 86400 long
+pub fn since_midnight_line(dt: DateTime<Tz>) -> String {
+    let secs = (dt - local_midnight(dt)).num_seconds();
+    format!("Since midnight: {secs}s ({})", dt.timezone())
+}
+
+// e.g. "3h 42m" or "2d 1h", ignoring sign; sub-minute gaps show seconds
+pub fn span(delta: TimeDelta) -> String {
+    let secs = delta.num_seconds().unsigned_abs();
+    let (d, h, m) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60);
+    let mut parts: Vec<String> = Vec::new();
+    if d > 0 {
+        parts.push(format!("{d}d"));
+    }
+    if h > 0 {
+        parts.push(format!("{h}h"));
+    }
+    if m > 0 {
+        parts.push(format!("{m}m"));
+    }
+    if parts.is_empty() {
+        parts.push(format!("{}s", secs % 60));
+    }
+    parts.join(" ")
+}
+
+// e.g. "+3h 42m" or "-2d 1h"
+pub fn signed_span(delta: TimeDelta) -> String {
+    let sign = if delta < TimeDelta::zero() { '-' } else { '+' };
+    format!("{sign}{}", span(delta))
+}
+
+// e.g. "in 3h 42m" or "2d 1h ago"
+pub fn humanize(delta: TimeDelta) -> String {
+    let span = span(delta);
+    if delta < TimeDelta::zero() { format!("{span} ago") } else { format!("in {span}") }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn fractional_offsets_show_minutes() {
+        let utc = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+        let output = Output::new(Format::Rfc3339, DISPLAY_FMT, false, None);
+        assert_eq!(output.local(utc.with_timezone(&Tz::Asia__Kathmandu)), "2025-06-01 05:45:00 +0545 (+05:45)");
+        assert_eq!(output.local(utc.with_timezone(&Tz::Australia__Adelaide)), "2025-06-01 09:30:00 ACST (+09:30)");
+        assert_eq!(utc.with_timezone(&Tz::Asia__Kathmandu).to_rfc3339(), "2025-06-01T05:45:00+05:45");
+        assert_eq!(utc.with_timezone(&Tz::Australia__Adelaide).to_rfc3339(), "2025-06-01T09:30:00+09:30");
+        let bare = Output::new(Format::Rfc3339, display_format(false, false, false), false, None);
+        assert_eq!(bare.local(utc.with_timezone(&Tz::Australia__Adelaide)), "2025-06-01 09:30:00 ACST");
+    }
+
+    #[test]
+    fn date_format_matches_the_date_command() {
+        let utc = Utc.with_ymd_and_hms(2025, 9, 2, 11, 0, 0).unwrap();
+        let output = Output::new(Format::Date, DISPLAY_FMT, false, None);
+        assert_eq!(output.utc(utc), "Tue Sep  2 11:00:00 UTC 2025");
+        assert_eq!(output.local(utc.with_timezone(&Tz::Australia__Brisbane)), "Tue Sep  2 21:00:00 AEST 2025");
+    }
+
+    #[test]
+    fn locale_translates_names() {
+        let utc = Utc.with_ymd_and_hms(2025, 9, 22, 11, 0, 0).unwrap();
+        let pattern = Format::custom("%A %e %B").unwrap();
+        let output = Output::new(pattern, DISPLAY_FMT, false, None).with_locale(Some(Locale::de_DE));
+        assert_eq!(output.utc(utc), "Montag 22 September");
+        let output = Output::new(Format::Date, DISPLAY_FMT, false, None).with_locale(Some(Locale::fr_FR));
+        assert_eq!(output.utc(utc), "lun. sept. 22 11:00:00 UTC 2025");
+        let output = Output::new(Format::Rfc2822, DISPLAY_FMT, false, None).with_locale(Some(Locale::fr_FR));
+        assert_eq!(output.utc(utc), "Mon, 22 Sep 2025 11:00:00 +0000");
+    }
+
+    #[test]
+    fn format_list_shows_each_keyword() {
+        let list = format_list();
+        assert_eq!(list.lines().count(), FORMAT_NAMES.len() + 1);
+        assert!(list.contains("rfc3339   2025-09-22T11:00:00+00:00\n"));
+        assert!(list.contains("ical      20250922T110000Z\n"));
+        assert!(Format::parse("help").is_err());
+    }
+
+    #[test]
+    fn ambiguous_json_lists_both_instants() {
+        let ny = Tz::America__New_York;
+        let (earliest, latest) = match ny.with_ymd_and_hms(2025, 11, 2, 1, 30, 0) {
+            chrono::LocalResult::Ambiguous(earliest, latest) => (earliest, latest),
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(
+            json_ambiguous(earliest, latest),
+            r#"{"local": "2025-11-02 01:30:00", "tz": "America/New_York", "ambiguous": ["2025-11-02T05:30:00+00:00", "2025-11-02T06:30:00+00:00"]}"#
+        );
+    }
+
+    #[test]
+    fn business_days_skip_weekends() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 9, d).unwrap();
+        // Monday 22nd to Friday 26th, then over the weekend to Monday 29th
+        assert_eq!(business_days(day(22), day(26)), 4);
+        assert_eq!(business_days(day(22), day(29)), 5);
+        assert_eq!(business_days(day(26), day(29)), 1);
+        assert_eq!(business_days(day(27), day(28)), 0);
+        assert_eq!(business_days(day(22), day(22)), 0);
+        assert_eq!(business_days(day(29), day(22)), -5);
+        assert_eq!(business_days(day(1), NaiveDate::from_ymd_opt(2025, 12, 31).unwrap()), 87);
+        assert_eq!(bizdays_line(day(22), day(24)), "Business days until: 2");
+    }
+
+    #[test]
+    fn am_pm_case_follows_the_flag() {
+        let local = Utc.with_ymd_and_hms(2025, 9, 22, 11, 0, 0).unwrap().with_timezone(&Tz::Australia__Brisbane);
+        let show = |lower| Output::new(Format::Rfc3339, display_format(true, true, lower), false, None).local(local);
+        assert_eq!(show(false), "2025-09-22 09:00:00 PM AEST (+10:00)");
+        assert_eq!(show(true), "2025-09-22 09:00:00 pm AEST (+10:00)");
+        assert_eq!(display_format(false, true, true), DISPLAY_FMT);
+    }
+
+    #[test]
+    fn transitions_show_both_clocks() {
+        let utc = Utc.with_ymd_and_hms(2025, 10, 4, 16, 0, 0).unwrap();
+        let offset = FixedOffset::east_opt(11 * 3600).unwrap();
+        assert_eq!(
+            transition_line(Tz::Australia__Sydney, utc, offset),
+            "2025-10-04 16:00:00 UTC -> +11:00 (AEDT, 2025-10-05 03:00:00 local)"
+        );
+    }
+
+    #[test]
+    fn dst_status_follows_the_season() {
+        let sydney = |m| Utc.with_ymd_and_hms(2025, m, 1, 0, 0, 0).unwrap().with_timezone(&Tz::Australia__Sydney);
+        assert_eq!(dst_status_line(sydney(1)), "DST status: daylight (AEDT)");
+        assert_eq!(dst_status_line(sydney(7)), "DST status: standard (AEST)");
+        let brisbane = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap().with_timezone(&Tz::Australia__Brisbane);
+        assert_eq!(dst_status_line(brisbane), "DST status: standard (AEST)");
+    }
+
+    #[test]
+    fn signed_spans_keep_the_sign() {
+        assert_eq!(signed_span(TimeDelta::hours(21)), "+21h");
+        assert_eq!(signed_span(TimeDelta::minutes(-(49 * 60 + 5))), "-2d 1h 5m");
+        assert_eq!(signed_span(TimeDelta::zero()), "+0s");
+    }
+
+    #[test]
+    fn custom_patterns_take_the_epoch() {
+        let utc = Utc.with_ymd_and_hms(2025, 9, 22, 11, 0, 0).unwrap();
+        let output = Output::new(Format::custom("%s (%H:%M %Z)").unwrap(), DISPLAY_FMT, false, None);
+        assert_eq!(output.utc(utc), "1758538800 (11:00 UTC)");
+        // The epoch names the instant, so it is the same on the local side
+        assert_eq!(output.local(utc.with_timezone(&Tz::Australia__Brisbane)), "1758538800 (21:00 AEST)");
+    }
+
+    #[test]
+    fn epoch_ms_counts_before_1970() {
+        let output = Output::new(Format::EpochMs, DISPLAY_FMT, false, None);
+        let utc = DateTime::from_timestamp(1_758_538_800, 250_000_000).unwrap();
+        assert_eq!(output.utc(utc), "1758538800250");
+        let utc = DateTime::from_timestamp(-1, 500_000_000).unwrap();
+        assert_eq!(output.utc(utc), "-500");
+    }
+
+    #[test]
+    fn zone_gaps_read_as_hours() {
+        let utc = DateTime::from_timestamp(1_758_538_800, 0).unwrap();
+        let line = |a, b| zone_gap_line(a, b, utc);
+        assert_eq!(line(Tz::Europe__London, Tz::Australia__Brisbane), "Europe/London is 9 hours behind Australia/Brisbane");
+        assert_eq!(line(Tz::Australia__Adelaide, Tz::UTC), "Australia/Adelaide is 9.5 hours ahead of UTC");
+        assert_eq!(line(Tz::Asia__Kathmandu, Tz::UTC), "Asia/Kathmandu is 5.75 hours ahead of UTC");
+        assert_eq!(line(Tz::Europe__London, Tz::UTC), "Europe/London is 1 hour ahead of UTC");
+        assert_eq!(line(Tz::Asia__Tokyo, Tz::Asia__Seoul), "Asia/Tokyo is at the same time as Asia/Seoul");
+    }
+
+    #[test]
+    fn epoch_formats_count_from_the_reference() {
+        let utc = DateTime::from_timestamp(1_758_538_800, 250_000_000).unwrap();
+        let j2000 = |format| Output::new(format, DISPLAY_FMT, false, None).with_epoch_reference(EpochReference::J2000);
+        assert_eq!(j2000(Format::Epoch).utc(utc), "811854000");
+        assert_eq!(j2000(Format::EpochMs).utc(utc), "811854000250");
+        let filetime = Output::new(Format::Epoch, DISPLAY_FMT, false, None).with_epoch_reference(EpochReference::FileTime);
+        assert_eq!(filetime.utc(utc), "134030124002500000");
+    }
+
+    #[test]
+    fn ical_stamps_have_no_separators() {
+        let output = Output::new(Format::Ical, DISPLAY_FMT, false, None);
+        // The stamp has no fractional part, so sub-second input is dropped
+        let utc = DateTime::from_timestamp(1_758_538_800, 750_000_000).unwrap();
+        assert_eq!(output.utc(utc), "20250922T110000Z");
+        let local = utc.with_timezone(&Tz::Australia__Brisbane);
+        assert_eq!(output.local(local), "2025-09-22 21:00:00 AEST (+10:00)");
+    }
+
+    #[test]
+    fn business_hours_need_a_weekday() {
+        let nine_to_five = (NaiveTime::from_hms_opt(9, 0, 0).unwrap(), NaiveTime::from_hms_opt(17, 0, 0).unwrap());
+        let at = |d, h| Tz::Europe__London.with_ymd_and_hms(2025, 9, d, h, 0, 0).unwrap();
+        assert_eq!(business_hours_line(at(22, 9), nine_to_five), "Within business hours: yes");
+        assert_eq!(business_hours_line(at(22, 17), nine_to_five), "Within business hours: no (outside 09:00-17:00)");
+        assert_eq!(business_hours_line(at(27, 12), nine_to_five), "Within business hours: no (Saturday)");
+        let night = (nine_to_five.1, nine_to_five.0);
+        assert_eq!(business_hours_line(at(22, 23), night), "Within business hours: yes");
+    }
+
+    #[test]
+    fn since_midnight_counts_real_seconds() {
+        let ny = Tz::America__New_York;
+        // 2025-03-09 loses an hour at 02:00, so 12:00 is only 11h in
+        let dt = ny.with_ymd_and_hms(2025, 3, 9, 12, 0, 0).unwrap();
+        assert_eq!(since_midnight_line(dt), "Since midnight: 39600s (America/New_York)");
+        let dt = ny.with_ymd_and_hms(2025, 11, 2, 12, 0, 0).unwrap();
+        assert_eq!(since_midnight_line(dt), "Since midnight: 46800s (America/New_York)");
+        // Santiago skips 00:00-01:00 when DST starts
+        let dt = Tz::America__Santiago.with_ymd_and_hms(2025, 9, 7, 2, 0, 0).unwrap();
+        assert_eq!(since_midnight_line(dt), "Since midnight: 3600s (America/Santiago)");
+    }
+
+    #[test]
+    fn precision_truncates_the_fraction() {
+        let utc = DateTime::from_timestamp(1_758_538_800, 123_456_789).unwrap();
+        let digits = |n| Output::new(Format::Rfc3339, DISPLAY_FMT, false, Some(n)).utc(utc);
+        assert_eq!(digits(0), "2025-09-22T11:00:00+00:00");
+        assert_eq!(digits(2), "2025-09-22T11:00:00.12+00:00");
+        assert_eq!(digits(3), "2025-09-22T11:00:00.123+00:00");
+        assert_eq!(digits(7), "2025-09-22T11:00:00.1234567+00:00");
+        assert_eq!(digits(9), "2025-09-22T11:00:00.123456789+00:00");
+    }
+
+    #[test]
+    fn precision_reaches_the_local_line() {
+        let local = DateTime::from_timestamp(1_758_538_800, 123_456_789).unwrap().with_timezone(&Tz::Australia__Brisbane);
+        let digits = |n| Output::new(Format::Rfc3339, DISPLAY_FMT, false, n).local(local);
+        assert_eq!(digits(None), "2025-09-22 21:00:00 AEST (+10:00)");
+        assert_eq!(digits(Some(0)), "2025-09-22 21:00:00 AEST (+10:00)");
+        assert_eq!(digits(Some(2)), "2025-09-22 21:00:00.12 AEST (+10:00)");
+        assert_eq!(digits(Some(9)), "2025-09-22 21:00:00.123456789 AEST (+10:00)");
+        let clock12 = Output::new(Format::Epoch, DISPLAY_FMT_12H, false, Some(3)).local(local);
+        assert_eq!(clock12, "2025-09-22 09:00:00.123 PM AEST (+10:00)");
+    }
+
+    #[test]
+    fn offset_table_sorts_by_offset() {
+        let utc = DateTime::from_timestamp(1_758_538_800, 0).unwrap();
+        let zones = [Tz::Asia__Tokyo, Tz::UTC, Tz::America__New_York, Tz::Europe__London, Tz::Etc__GMT];
+        assert_eq!(
+            offset_table(utc, &zones),
+            [
+                "-04:00  America/New_York  Mon 2025-09-22 07:00 EDT",
+                "+00:00  UTC               Mon 2025-09-22 11:00 UTC",
+                "+00:00  Etc/GMT           Mon 2025-09-22 11:00 GMT",
+                "+01:00  Europe/London     Mon 2025-09-22 12:00 BST",
+                "+09:00  Asia/Tokyo        Mon 2025-09-22 20:00 JST",
+            ]
+        );
+    }
+
+    #[test]
+    fn utc_designator_picks_z_or_offset() {
+        let utc = DateTime::from_timestamp(1_758_538_800, 500_000_000).unwrap();
+        let output = |precision| Output::new(Format::Rfc3339, DISPLAY_FMT, false, precision);
+        assert_eq!(output(None).utc(utc), "2025-09-22T11:00:00.500+00:00");
+        assert_eq!(output(None).with_utc_z(true).utc(utc), "2025-09-22T11:00:00.500Z");
+        assert_eq!(output(Some(0)).with_utc_z(true).utc(utc), "2025-09-22T11:00:00Z");
+        assert_eq!(output(Some(2)).with_utc_z(true).utc(utc), "2025-09-22T11:00:00.50Z");
+    }
+
+    #[test]
+    fn format_lists_label_each_line_in_order() {
+        let utc = DateTime::from_timestamp(1_758_538_800, 0).unwrap();
+        let list = Format::parse_list("ical, epoch,rfc3339").unwrap();
+        let output = Output::new(list[0].clone(), DISPLAY_FMT, false, None).with_format_list(list);
+        assert_eq!(
+            output.utc_lines(utc),
+            ["UTC (ical): 20250922T110000Z", "UTC (epoch): 1758538800", "UTC (rfc3339): 2025-09-22T11:00:00+00:00"]
+        );
+        let single = Output::new(Format::Epoch, DISPLAY_FMT, false, None).with_format_list(vec![Format::Epoch]);
+        assert_eq!(single.utc_lines(utc), ["UTC: 1758538800"]);
+        assert!(Format::parse_list("epoch,,ical").is_err());
+    }
+}
@@ -0,0 +1,559 @@
+//! End-to-end runs of the binary, pinning the exit codes and output lines
+//! that scripts depend on.
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::Stdio;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use predicates::str::contains;
+
+// A run that ignores the caller's config file and zone settings
+fn utc_time() -> Command {
+    let mut cmd = Command::cargo_bin("utc_time").unwrap();
+    cmd.env("XDG_CONFIG_HOME", "/nonexistent").env_remove("UTC_TIME_TZ").env_remove("TZ");
+    cmd.args(["--tz", "Australia/Brisbane"]);
+    cmd
+}
+
+#[test]
+fn choice_1_reads_the_input_as_local() {
+    utc_time()
+        .args(["--choice", "1", "21:00", "22-09-2025"])
+        .assert()
+        .success()
+        .stdout(contains("UTC: 2025-09-22T11:00:00+00:00\n"))
+        .stdout(contains("Australia/Brisbane: 2025-09-22 21:00:00 AEST (+10:00)\n"));
+}
+
+#[test]
+fn choice_2_reads_the_input_as_utc() {
+    utc_time()
+        .args(["--choice", "2", "21:00 22-09-2025"])
+        .assert()
+        .success()
+        .stdout(contains("UTC: 2025-09-22T21:00:00+00:00\n"))
+        .stdout(contains("Australia/Brisbane: 2025-09-23 07:00:00 AEST (+10:00)\n"));
+}
+
+#[test]
+fn invalid_choice_exits_6() {
+    utc_time()
+        .args(["--choice", "3", "21:00 22-09-2025"])
+        .assert()
+        .code(6)
+        .stdout("")
+        .stderr(contains("Invalid choice '3', expected '1' or '2'"));
+}
+
+#[test]
+fn parse_errors_exit_3() {
+    utc_time()
+        .args(["--choice", "1", "25:00 22-09-2025"])
+        .assert()
+        .code(3)
+        .stdout("")
+        .stderr("Parse error: hour must be 0–23, got 25\n");
+}
+
+#[test]
+fn empty_input_is_a_missing_argument() {
+    for blank in ["", "   "] {
+        utc_time()
+            .args(["--choice", "1", blank])
+            .assert()
+            .code(1)
+            .stdout("")
+            .stderr(predicates::str::starts_with("No time provided\nUsage: utc_time"));
+    }
+}
+
+#[test]
+fn chain_shows_one_instant_per_hop() {
+    utc_time()
+        .args(["--chain", "Asia/Tokyo>Europe/London>America/New_York", "21:00 22-09-2025"])
+        .assert()
+        .success()
+        .stdout(
+            "1. Asia/Tokyo        2025-09-22 21:00:00 JST (+09:00)\n\
+             2. Europe/London     2025-09-22 13:00:00 BST (+01:00)\n\
+             3. America/New_York  2025-09-22 08:00:00 EDT (-04:00)\n",
+        );
+}
+
+#[test]
+fn log_file_appends_one_line_per_conversion() {
+    let path = std::env::temp_dir().join(format!("utc_time-log-{}.tsv", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    for _ in 0..2 {
+        utc_time().args(["--choice", "1", "--log-file"]).arg(&path).arg("21:00 22-09-2025").assert().success();
+    }
+    let log = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 2);
+    for line in lines {
+        let fields: Vec<&str> = line.split('\t').collect();
+        assert_eq!(
+            fields[1..],
+            ["21:00 22-09-2025", "2025-09-22T11:00:00+00:00", "2025-09-22T21:00:00+10:00", "Australia/Brisbane"]
+        );
+    }
+    // Modes that convert line by line log each line they convert
+    utc_time().args(["--csv", "--column", "2", "--log-file"]).arg(&path).write_stdin("id,when\n1,21:00 22-09-2025\n").assert().success();
+    utc_time().args(["--repl", "--to-utc", "--log-file"]).arg(&path).write_stdin("21:00 22-09-2025\nquit\n").assert().success();
+    let log = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let inputs: Vec<&str> = log.lines().map(|line| line.split('\t').nth(1).unwrap()).collect();
+    assert_eq!(inputs, ["21:00 22-09-2025"; 2]);
+    utc_time()
+        .args(["--log-file", "/nonexistent/utc_time.log", "now"])
+        .assert()
+        .code(10)
+        .stdout("")
+        .stderr(contains("Cannot open log file /nonexistent/utc_time.log"));
+    // Nothing is converted, so the log is never opened
+    utc_time().args(["--log-file", "/nonexistent/utc_time.log", "--help"]).assert().success();
+}
+
+#[test]
+fn crlf_batch_lines_parse() {
+    let lines = "21:00 22-09-2025\r\n9:00 PM 22-09-2025 \r\n\r\n21:00 22/09/2025\r";
+    let expected = "UTC: 2025-09-22T11:00:00+00:00\n".repeat(3);
+    utc_time().arg("--stdin").write_stdin(lines).assert().success().stdout(expected.clone());
+    let path = std::env::temp_dir().join(format!("utc_time-crlf-{}.txt", std::process::id()));
+    std::fs::write(&path, lines).unwrap();
+    let run = utc_time().arg("--input-file").arg(&path).assert();
+    std::fs::remove_file(&path).unwrap();
+    run.success().stdout(expected);
+}
+
+#[test]
+fn when_reads_the_time_in_the_first_zone() {
+    utc_time()
+        .args(["--when", "09:00 22-09-2025 Brisbane in Europe/London"])
+        .assert()
+        .success()
+        .stdout(
+            "Australia/Brisbane: 2025-09-22 09:00:00 AEST (+10:00)\n\
+             Europe/London: 2025-09-22 00:00:00 BST (+01:00)\n\
+             Offset: +01:00 (BST, DST: yes)\n",
+        );
+}
+
+#[test]
+fn recurrence_marks_weeks_an_offset_change_moves() {
+    utc_time()
+        .args(["--from", "Australia/Sydney", "--to", "Europe/London", "--recurrence", "weekly", "--count", "3"])
+        .arg("18:00 22-09-2025")
+        .assert()
+        .success()
+        .stdout(
+            "Week 1: 2025-09-22 09:00:00 BST (+01:00)\n\
+             Week 2: 2025-09-29 09:00:00 BST (+01:00)\n\
+             Week 3: 2025-10-06 08:00:00 BST (+01:00)  <- moved 1h earlier\n",
+        );
+    // Brisbane keeps one offset all year, so nothing moves
+    utc_time()
+        .args(["--choice", "1", "--recurrence", "weekly", "--count", "2", "18:00 22-09-2025"])
+        .assert()
+        .success()
+        .stdout("Week 1: 2025-09-22 08:00:00 UTC (+00:00)\nWeek 2: 2025-09-29 08:00:00 UTC (+00:00)\n");
+}
+
+#[test]
+fn too_many_args_exit_2() {
+    utc_time()
+        .args(["21:00", "22-09-2025", "extra"])
+        .assert()
+        .code(2)
+        .stderr(contains("Too many arguments"));
+}
+
+#[test]
+fn relative_to_reads_the_reference_like_the_input() {
+    utc_time()
+        .args(["--choice", "1", "--relative-to", "2025-09-22T00:00:00Z", "21:00 22-09-2025"])
+        .assert()
+        .success()
+        .stdout(contains("Relative: +11h\n"));
+    // --add moves the result, not the reference
+    utc_time()
+        .args(["--choice", "1", "--add", "1h", "--relative-to", "22:00 22-09-2025", "21:00 22-09-2025"])
+        .assert()
+        .success()
+        .stdout(contains("Relative: +0s\n"));
+}
+
+#[test]
+fn format_list_needs_no_time() {
+    utc_time().arg("--list-formats").assert().success().stdout(contains("epoch     1758538800\n"));
+    utc_time().args(["--format", "help"]).assert().success().stdout(contains("date      Mon Sep 22"));
+}
+
+#[test]
+fn mistyped_zones_get_a_suggestion() {
+    utc_time()
+        .args(["--to", "Australia/Sydney2", "21:00 22-09-2025"])
+        .assert()
+        .code(7)
+        .stderr("Unknown timezone 'Australia/Sydney2'; did you mean Australia/Sydney?\n");
+}
+
+#[test]
+fn ambiguous_json_lists_both_instants() {
+    Command::cargo_bin("utc_time")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", "/nonexistent")
+        .args(["--tz", "America/New_York", "--json", "--choice", "1", "01:30 02-11-2025"])
+        .assert()
+        .code(5)
+        .stdout(contains(r#""ambiguous": ["2025-11-02T05:30:00+00:00", "2025-11-02T06:30:00+00:00"]"#));
+}
+
+#[test]
+fn csv_columns_convert_in_place_or_appended() {
+    let records = "id,when\n1,21:00 22-09-2025\n2,\"9:00 PM, 22-09-2025\"\n3,not a time\n";
+    utc_time()
+        .args(["--csv", "--column", "2"])
+        .write_stdin(records)
+        .assert()
+        .code(3)
+        .stdout("id,when\n1,2025-09-22T11:00:00+00:00\n2,\"9:00 PM, 22-09-2025\"\n3,not a time\n")
+        .stderr(contains("line 3: Parse error").and(contains("line 4: Parse error")));
+    utc_time()
+        .args(["--csv", "--column", "2", "--append-column"])
+        .write_stdin("id,when\n1,21:00 22-09-2025\n")
+        .assert()
+        .success()
+        .stdout("id,when,UTC\n1,21:00 22-09-2025,2025-09-22T11:00:00+00:00\n");
+}
+
+#[test]
+fn piped_input_is_read_without_a_menu() {
+    utc_time()
+        .write_stdin("21:00 22-09-2025\n")
+        .assert()
+        .success()
+        .stdout(contains("UTC: 2025-09-22T11:00:00+00:00\n"))
+        .stderr(contains("Select conversion").not());
+    utc_time().write_stdin(&b"\xff\n"[..]).assert().code(8).stderr(contains("stdin: stream did not contain valid UTF-8"));
+}
+
+#[test]
+fn input_tz_only_reads_choice_1() {
+    utc_time()
+        .args(["--input-tz", "Asia/Tokyo", "--choice", "1", "21:00 22-09-2025"])
+        .assert()
+        .success()
+        .stdout(contains("UTC: 2025-09-22T12:00:00+00:00\n"))
+        .stdout(contains("Asia/Tokyo: 2025-09-22 21:00:00 JST (+09:00)\n"));
+    utc_time()
+        .args(["--input-tz", "Asia/Tokyo", "--choice", "2", "21:00 22-09-2025"])
+        .assert()
+        .success()
+        .stdout(contains("Australia/Brisbane: 2025-09-23 07:00:00 AEST (+10:00)\n"));
+}
+
+#[test]
+fn batch_output_stops_quietly_at_a_closed_pipe() {
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("utc_time"))
+        .env("XDG_CONFIG_HOME", "/nonexistent")
+        .args(["--tz", "Australia/Brisbane", "--stdin"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = std::thread::spawn(move || {
+        // The child may be gone before all of this is written
+        for _ in 0..100_000 {
+            if stdin.write_all(b"21:00 22-09-2025\n").is_err() {
+                break;
+            }
+        }
+    });
+    // Read one line, then hang up like `head -1`
+    let mut first = String::new();
+    BufReader::new(child.stdout.take().unwrap()).read_line(&mut first).unwrap();
+    assert_eq!(first, "UTC: 2025-09-22T11:00:00+00:00\n");
+    let result = child.wait_with_output().unwrap();
+    writer.join().unwrap();
+    assert_eq!(result.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&result.stderr), "");
+}
+
+#[test]
+fn long_listings_stop_quietly_at_a_closed_pipe() {
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("utc_time"))
+        .env("XDG_CONFIG_HOME", "/nonexistent")
+        .args(["--tz", "UTC", "--to-utc", "--recurrence", "daily", "--count", "200000", "21:00 22-09-2025"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut first = String::new();
+    BufReader::new(child.stdout.take().unwrap()).read_line(&mut first).unwrap();
+    assert!(first.starts_with("Day 1: "), "{first}");
+    let result = child.wait_with_output().unwrap();
+    assert_eq!(result.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&result.stderr), "");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn a_failed_write_is_reported_not_a_panic() {
+    let result = std::process::Command::new(assert_cmd::cargo::cargo_bin("utc_time"))
+        .env("XDG_CONFIG_HOME", "/nonexistent")
+        .args(["--tz", "Australia/Brisbane", "--to-utc", "21:00 22-09-2025"])
+        .stdout(std::fs::File::create("/dev/full").unwrap())
+        .output()
+        .unwrap();
+    assert_eq!(result.status.code(), Some(11));
+    assert_eq!(String::from_utf8_lossy(&result.stderr), "cannot write to stdout: No space left on device (os error 28)\n");
+}
+
+#[test]
+fn epoch_reference_applies_to_input_and_output() {
+    utc_time()
+        .args(["--epoch-reference", "j2000", "--quiet", "811854000"])
+        .assert()
+        .success()
+        .stdout("UTC: 2025-09-22T11:00:00+00:00\n");
+    utc_time()
+        .args(["--epoch-reference", "filetime", "--format", "epoch", "--quiet", "2025-09-22T11:00:00Z"])
+        .assert()
+        .success()
+        .stdout("UTC: 134030124000000000\n");
+    utc_time().args(["--epoch-reference", "gps", "now"]).assert().code(1);
+}
+
+#[test]
+fn noon_stands_in_for_the_time() {
+    utc_time()
+        .args(["--to-utc", "--quiet", "noon 22-09-2025"])
+        .assert()
+        .success()
+        .stdout("UTC: 2025-09-22T02:00:00+00:00\n");
+}
+
+#[test]
+fn diff_zones_compares_offsets_now() {
+    // Neither zone has DST, so the answer holds all year
+    utc_time()
+        .args(["--diff-zones", "Asia/Kolkata", "Asia/Tokyo"])
+        .assert()
+        .success()
+        .stdout("Asia/Kolkata is 3.5 hours behind Asia/Tokyo\n");
+    utc_time()
+        .args(["--json", "--diff-zones", "Asia/Tokyo", "UTC"])
+        .assert()
+        .success()
+        .stdout("{\"a\": \"Asia/Tokyo\", \"b\": \"UTC\", \"difference_seconds\": 32400}\n");
+    utc_time().args(["--diff-zones", "Asia/Tokyo", "Mars/Olympus"]).assert().code(7);
+}
+
+#[cfg(not(feature = "clipboard"))]
+#[test]
+fn clipboard_needs_the_feature() {
+    utc_time()
+        .arg("--from-clipboard")
+        .assert()
+        .code(1)
+        .stderr(contains("--from-clipboard needs a build with --features clipboard"));
+}
+
+// A stand-in xclip on PATH plays the clipboard
+#[cfg(all(feature = "clipboard", target_os = "linux"))]
+#[test]
+fn clipboard_text_is_parsed() {
+    use std::os::unix::fs::PermissionsExt;
+    let paste = |text: &str| {
+        let dir = std::env::temp_dir().join(format!("utc_time_clipboard_{}_{}", std::process::id(), text.len()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let xclip = dir.join("xclip");
+        std::fs::write(&xclip, format!("#!/bin/sh\nprintf '{text}'\n")).unwrap();
+        std::fs::set_permissions(&xclip, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let mut cmd = utc_time();
+        cmd.env("PATH", &dir).args(["--from-clipboard", "--to-utc", "--quiet"]);
+        cmd
+    };
+    paste("21:00 22-09-2025\\n").assert().success().stdout("UTC: 2025-09-22T11:00:00+00:00\n");
+    paste("").assert().code(3).stderr(contains("Parse error: no time provided"));
+    paste("not a time").assert().code(3).stderr(contains("Parse error"));
+}
+
+#[test]
+fn years_at_the_pivot_are_spelled_out() {
+    utc_time()
+        .args(["--to-utc", "21:00 22-09-68"])
+        .assert()
+        .success()
+        .stdout(contains("UTC: 2068-09-22T11:00:00+00:00"))
+        .stderr(contains("note: interpreted year 68 as 2068"))
+        .stderr(contains("century cutoff").not());
+    utc_time()
+        .args(["--to-utc", "--verbose", "21:00 22-09-69"])
+        .assert()
+        .success()
+        .stdout(contains("UTC: 1969-09-22T11:00:00+00:00"))
+        .stderr(contains("note: 69 is at the century cutoff: 68 and below are 20xx, 69 and above 19xx"));
+    utc_time()
+        .args(["--to-utc", "--verbose", "21:00 22-09-50"])
+        .assert()
+        .success()
+        .stderr(contains("century cutoff").not());
+}
+
+#[test]
+fn ical_output_reads_back() {
+    utc_time()
+        .args(["--format", "ical", "--quiet", "20250922T110000Z"])
+        .assert()
+        .success()
+        .stdout("UTC: 20250922T110000Z\n");
+}
+
+#[test]
+fn no_newline_trims_only_the_last_line() {
+    utc_time()
+        .args(["--to-utc", "--quiet", "--no-newline", "21:00 22-09-2025"])
+        .assert()
+        .success()
+        .stdout("UTC: 2025-09-22T11:00:00+00:00");
+    utc_time()
+        .args(["--no-newline", "--stdin"])
+        .write_stdin("21:00 22-09-2025\n09:00 23-09-2025\n")
+        .assert()
+        .success()
+        .stdout("UTC: 2025-09-22T11:00:00+00:00\nUTC: 2025-09-22T23:00:00+00:00");
+}
+
+#[test]
+fn group_by_date_sorts_under_headings() {
+    utc_time()
+        .args(["--stdin", "--group-by-date", "--to-local"])
+        .write_stdin("20:00 23-09-2025\n13:00 22-09-2025\nnonsense\n23:00 22-09-2025\n")
+        .assert()
+        .code(3)
+        .stdout(
+            "Mon 2025-09-22\n  Australia/Brisbane: 2025-09-22 23:00:00 AEST (+10:00)\n\
+             Tue 2025-09-23\n  Australia/Brisbane: 2025-09-23 09:00:00 AEST (+10:00)\n\
+             Wed 2025-09-24\n  Australia/Brisbane: 2025-09-24 06:00:00 AEST (+10:00)\n",
+        )
+        .stderr(contains("line 3: Parse error"));
+    utc_time().args(["--group-by-date", "21:00"]).assert().code(1);
+}
+
+#[test]
+fn batch_lines_honour_the_parse_options() {
+    utc_time()
+        .args(["--stdin", "--quiet", "--format", "rfc3339", "--year-pivot", "20", "--base-date", "01-03-2025"])
+        .write_stdin("21:00 22-09-2025\n21:00 22-09-25\n21:00\n")
+        .assert()
+        .success()
+        .stdout("UTC: 2025-09-22T11:00:00+00:00\nUTC: 1925-09-22T11:00:00+00:00\nUTC: 2025-03-01T11:00:00+00:00\n");
+}
+
+#[test]
+fn echo_input_ties_each_result_to_its_line() {
+    utc_time()
+        .args(["--stdin", "--echo-input", "--format", "rfc3339"])
+        .write_stdin("21:00 22-09-2025\n\n  09:00 23-09-2025  \n")
+        .assert()
+        .success()
+        .stdout("21:00 22-09-2025 -> UTC: 2025-09-22T11:00:00+00:00\n09:00 23-09-2025 -> UTC: 2025-09-22T23:00:00+00:00\n");
+    utc_time()
+        .args(["--stdin", "--echo-input", "--json"])
+        .write_stdin("21:00 22-09-2025\n")
+        .assert()
+        .success()
+        .stdout(contains("{\"input\": \"21:00 22-09-2025\", \"utc\": \"2025-09-22T11:00:00+00:00\""));
+    utc_time().args(["--echo-input", "21:00"]).assert().code(1);
+}
+
+#[test]
+fn assume_year_moves_a_bare_time() {
+    utc_time()
+        .args(["--to-utc", "--quiet", "--base-date", "31-12-2025", "--assume-year", "2026", "09:00"])
+        .assert()
+        .success()
+        .stdout("UTC: 2026-12-30T23:00:00+00:00\n");
+}
+
+#[test]
+fn ambiguous_show_converts_each_field_order() {
+    utc_time()
+        .args(["--to-local", "--ambiguous", "show", "11:00 01-02-03"])
+        .assert()
+        .success()
+        .stdout(contains("As 2003-02-01 (%H:%M %d-%m-%Y):\n  UTC: 2003-02-01T11:00:00+00:00\n"))
+        .stdout(contains("As 2003-01-02 (%H:%M %m-%d-%Y):\n"))
+        .stdout(contains("As 2001-02-03 (%H:%M %Y-%m-%d):\n"))
+        .stderr(contains("meant year first").not());
+    // Only one order makes a date, so it converts as usual
+    utc_time()
+        .args(["--to-utc", "--quiet", "--ambiguous", "show", "21:00 22-09-2025"])
+        .assert()
+        .success()
+        .stdout("UTC: 2025-09-22T11:00:00+00:00\n");
+    utc_time().args(["--ambiguous", "guess", "21:00"]).assert().code(1);
+}
+
+#[test]
+fn military_letters_name_the_offset() {
+    utc_time()
+        .args(["--quiet", "21:00K 22-09-2025"])
+        .assert()
+        .success()
+        .stdout("UTC: 2025-09-22T11:00:00+00:00\n");
+}
+
+#[test]
+fn repl_reads_choice_one_in_the_input_zone() {
+    utc_time()
+        .args(["--repl", "--input-tz", "Asia/Tokyo", "--to-utc", "--format", "rfc3339"])
+        .write_stdin("21:00 22-09-2025\nquit\n")
+        .assert()
+        .success()
+        .stdout("UTC: 2025-09-22T12:00:00+00:00\n");
+    utc_time()
+        .args(["--repl", "--input-tz", "Asia/Tokyo", "--format", "rfc3339"])
+        .write_stdin("21:00 22-09-2025\n1\nquit\n")
+        .assert()
+        .success()
+        .stdout("UTC: 2025-09-22T12:00:00+00:00\n")
+        .stderr(contains("1) Asia/Tokyo -> UTC"));
+}
+
+#[test]
+fn batch_modes_read_in_the_input_zone() {
+    utc_time()
+        .args(["--stdin", "--to-utc", "--input-tz", "Asia/Tokyo", "--format", "rfc3339"])
+        .write_stdin("21:00 22-09-2025\n")
+        .assert()
+        .success()
+        .stdout("UTC: 2025-09-22T12:00:00+00:00\n");
+    utc_time()
+        .args(["--csv", "--column", "2", "--to-utc", "--input-tz", "Asia/Tokyo"])
+        .write_stdin("id,when\n1,21:00 22-09-2025\n")
+        .assert()
+        .success()
+        .stdout("id,when\n1,2025-09-22T12:00:00+00:00\n");
+}
+
+#[test]
+fn an_unreadable_repl_line_is_an_error() {
+    utc_time().args(["--repl"]).write_stdin(&b"\xff\n"[..]).assert().code(8).stderr(contains("stdin: stream did not contain valid UTF-8"));
+}
+
+#[test]
+fn an_unreadable_choice_is_an_error() {
+    utc_time()
+        .args(["--repl"])
+        .write_stdin(&b"21:00 22-09-2025\n\xff\n"[..])
+        .assert()
+        .code(9)
+        .stderr(contains("Cannot read the choice"));
+}